@@ -0,0 +1,296 @@
+use std::collections::HashMap;
+
+use regex::Regex;
+use serde::Serialize;
+use sqlx::MySqlPool;
+
+use super::model::{Alias, AliasError};
+use crate::database::{fetch_all_by_ids, Executor};
+use crate::instance::Instance;
+use crate::uuid::{
+    Attachment, BlogPost, Entity, EntityRevision, Page, PageRevision, TaxonomyTerm, User, Uuid,
+    UuidError, UuidFetcher,
+};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UuidAliases {
+    pub aliases: Vec<UuidAlias>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UuidAlias {
+    pub id: i32,
+    pub path: String,
+}
+
+impl UuidAliases {
+    pub async fn fetch(ids: &[i32], pool: &MySqlPool) -> Result<Self, AliasError> {
+        Self::fetch_via_transaction(ids, pool).await
+    }
+
+    /// Fetches the canonical aliases of the given UUIDs without loading the UUIDs themselves.
+    /// UUIDs that do not exist are left out.
+    pub async fn fetch_via_transaction<'a, E>(ids: &[i32], executor: E) -> Result<Self, AliasError>
+    where
+        E: Executor<'a>,
+    {
+        if ids.is_empty() {
+            return Ok(UuidAliases { aliases: vec![] });
+        }
+
+        let mut transaction = executor.begin().await?;
+
+        let uuids: Vec<(i64, String)> = fetch_all_by_ids(
+            r#"SELECT id, discriminator FROM uuid WHERE id IN ({})"#,
+            ids,
+            &mut transaction,
+        )
+        .await?;
+        let mut ids_by_discriminator: HashMap<String, Vec<i32>> = HashMap::new();
+        for (id, discriminator) in uuids {
+            ids_by_discriminator
+                .entry(discriminator)
+                .or_insert_with(Vec::new)
+                .push(id as i32);
+        }
+
+        // The aliases are computed by the same functions `Uuid::fetch` uses.
+        let mut paths: HashMap<i32, String> = HashMap::new();
+        for (discriminator, ids) in ids_by_discriminator.iter() {
+            let aliases = match discriminator.as_str() {
+                "attachment" => {
+                    Attachment::fetch_aliases_via_transaction(ids, &mut transaction).await?
+                }
+                "blogPost" => {
+                    BlogPost::fetch_aliases_via_transaction(ids, &mut transaction).await?
+                }
+                "entity" => Entity::fetch_aliases_via_transaction(ids, &mut transaction).await?,
+                "entityRevision" => {
+                    EntityRevision::fetch_aliases_via_transaction(ids, &mut transaction).await?
+                }
+                "page" => Page::fetch_aliases_via_transaction(ids, &mut transaction).await?,
+                "pageRevision" => {
+                    PageRevision::fetch_aliases_via_transaction(ids, &mut transaction).await?
+                }
+                "taxonomyTerm" => {
+                    TaxonomyTerm::fetch_aliases_via_transaction(ids, &mut transaction).await?
+                }
+                "user" => User::fetch_aliases_via_transaction(ids, &mut transaction).await?,
+                // The alias of comments depends on the object they belong to, so we fall back to
+                // fetching them one by one.
+                _ => {
+                    let mut aliases = HashMap::new();
+                    for id in ids {
+                        match Uuid::fetch_via_transaction(*id, &mut transaction).await {
+                            Ok(uuid) => {
+                                aliases.insert(*id, uuid.get_alias());
+                            }
+                            Err(UuidError::DatabaseError { inner }) => return Err(inner.into()),
+                            Err(_) => {}
+                        }
+                    }
+                    aliases
+                }
+            };
+            paths.extend(aliases);
+        }
+
+        transaction.commit().await?;
+
+        let aliases = ids
+            .iter()
+            .filter_map(|id| {
+                paths.get(id).map(|path| UuidAlias {
+                    id: *id,
+                    path: path.clone(),
+                })
+            })
+            .collect();
+
+        Ok(UuidAliases { aliases })
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathAliases {
+    pub instance: Instance,
+    pub aliases: Vec<PathAlias>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathAlias {
+    /// The path as requested.
+    pub path: String,
+    pub id: i32,
+    /// The canonical alias of the UUID the path resolves to.
+    pub alias: String,
+}
+
+impl PathAliases {
+    pub async fn fetch(
+        paths: &[String],
+        instance: Instance,
+        pool: &MySqlPool,
+    ) -> Result<Self, AliasError> {
+        Self::fetch_via_transaction(paths, instance, pool).await
+    }
+
+    /// Resolves the given paths to canonical aliases. Paths containing the id of a UUID (like
+    /// `/123` or `/math/123/title`) are resolved in bulk, all other paths one by one. Paths that
+    /// do not resolve to a UUID are left out.
+    pub async fn fetch_via_transaction<'a, E>(
+        paths: &[String],
+        instance: Instance,
+        executor: E,
+    ) -> Result<Self, AliasError>
+    where
+        E: Executor<'a>,
+    {
+        let re = Regex::new(r"^(?:(?:[^/]+/)?(?P<id>\d+)/[^/]*|(?P<short_id>\d+))$").unwrap();
+
+        let mut transaction = executor.begin().await?;
+
+        let mut ids_by_path: HashMap<&String, i32> = HashMap::new();
+        let mut resolved_paths: HashMap<&String, (i32, String)> = HashMap::new();
+
+        for path in paths {
+            let trimmed_path = path.trim_start_matches('/');
+            let id = re.captures(trimmed_path).and_then(|captures| {
+                captures
+                    .name("id")
+                    .or_else(|| captures.name("short_id"))
+                    .and_then(|id| id.as_str().parse::<i32>().ok())
+            });
+            match id {
+                Some(id) => {
+                    ids_by_path.insert(path, id);
+                }
+                None => {
+                    match Alias::fetch_via_transaction(
+                        trimmed_path,
+                        instance.clone(),
+                        &mut transaction,
+                    )
+                    .await
+                    {
+                        Ok(alias) => {
+                            resolved_paths.insert(path, (alias.id, alias.path));
+                        }
+                        Err(AliasError::DatabaseError { inner }) => {
+                            return Err(AliasError::DatabaseError { inner })
+                        }
+                        Err(_) => {}
+                    }
+                }
+            }
+        }
+
+        let mut ids: Vec<i32> = ids_by_path.values().copied().collect();
+        ids.sort_unstable();
+        ids.dedup();
+        let uuid_aliases = UuidAliases::fetch_via_transaction(&ids, &mut transaction).await?;
+        let uuid_aliases: HashMap<i32, String> = uuid_aliases
+            .aliases
+            .into_iter()
+            .map(|alias| (alias.id, alias.path))
+            .collect();
+
+        transaction.commit().await?;
+
+        for (path, id) in ids_by_path {
+            if let Some(alias) = uuid_aliases.get(&id) {
+                resolved_paths.insert(path, (id, alias.clone()));
+            }
+        }
+
+        let aliases = paths
+            .iter()
+            .filter_map(|path| {
+                resolved_paths.get(path).map(|(id, alias)| PathAlias {
+                    path: path.clone(),
+                    id: *id,
+                    alias: alias.clone(),
+                })
+            })
+            .collect();
+
+        Ok(PathAliases { instance, aliases })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PathAliases, UuidAliases};
+    use crate::create_database_pool;
+    use crate::instance::Instance;
+    use crate::uuid::{Uuid, UuidFetcher};
+
+    #[actix_rt::test]
+    async fn uuid_aliases_match_uuid_alias() {
+        let pool = create_database_pool().await.unwrap();
+        let mut ids = vec![1, 1855, 1565, 35573];
+        // Attachments with more than one file and exercises linked via their parent.
+        let attachment = sqlx::query!(
+            r#"
+                SELECT attachment_id FROM attachment_file
+                    GROUP BY attachment_id
+                    HAVING COUNT(*) > 1
+                    LIMIT 1
+            "#
+        )
+        .fetch_optional(&pool)
+        .await
+        .unwrap();
+        ids.extend(attachment.map(|attachment| attachment.attachment_id as i32));
+        let grouped_exercise = sqlx::query!(r#"SELECT child_id FROM entity_link LIMIT 1"#)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        ids.push(grouped_exercise.child_id as i32);
+        // Taxonomy terms on different levels of the taxonomy.
+        let taxonomy_terms = sqlx::query!(
+            r#"
+                SELECT tt.id FROM term_taxonomy tt
+                    JOIN uuid u ON u.id = tt.id
+                    WHERE u.trashed = 0
+                    ORDER BY tt.id
+                    LIMIT 20
+            "#
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        ids.extend(taxonomy_terms.iter().map(|term| term.id as i32));
+        ids.sort_unstable();
+        ids.dedup();
+
+        let aliases = UuidAliases::fetch(&ids, &pool).await.unwrap();
+
+        assert_eq!(aliases.aliases.len(), ids.len());
+        for alias in aliases.aliases {
+            let uuid = Uuid::fetch(alias.id, &pool).await.unwrap();
+            assert_eq!(alias.path, uuid.get_alias());
+        }
+    }
+
+    #[actix_rt::test]
+    async fn path_aliases() {
+        let pool = create_database_pool().await.unwrap();
+        let paths = vec!["/1855".to_string(), "/mathe/1855/foo".to_string()];
+
+        let aliases = PathAliases::fetch(&paths, Instance::De, &pool)
+            .await
+            .unwrap();
+
+        let uuid = Uuid::fetch(1855, &pool).await.unwrap();
+        assert_eq!(aliases.aliases.len(), 2);
+        for alias in aliases.aliases {
+            assert_eq!(alias.id, 1855);
+            assert_eq!(alias.alias, uuid.get_alias());
+        }
+    }
+}
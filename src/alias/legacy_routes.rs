@@ -0,0 +1,179 @@
+use std::env;
+use std::fs;
+
+use once_cell::sync::OnceCell;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::instance::Instance;
+
+/// Rules that are used when `LEGACY_ROUTES_FILE` is not set.
+const DEFAULT_LEGACY_ROUTES: &str = include_str!("legacy_routes.json");
+
+static LEGACY_ROUTES: OnceCell<LegacyRoutes> = OnceCell::new();
+
+/// A path (without leading slash) that is still served by the legacy serlo.org application and
+/// thus must never resolve to an alias.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LegacyRouteRule {
+    #[serde(rename = "type")]
+    pub rule_type: LegacyRouteRuleType,
+    pub value: String,
+    /// Restricts the rule to one instance. Rules without an instance apply to all instances.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<Instance>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LegacyRouteRuleType {
+    Exact,
+    Prefix,
+    Regex,
+}
+
+#[derive(Error, Debug)]
+pub enum LegacyRoutesError {
+    #[error("Legacy routes cannot be loaded: {inner:?}.")]
+    IoError { inner: std::io::Error },
+    #[error("Legacy routes are invalid: {inner:?}.")]
+    InvalidConfiguration { inner: serde_json::Error },
+    #[error("Legacy route {value:?} is not a valid regex: {inner:?}.")]
+    InvalidRegex { value: String, inner: regex::Error },
+}
+
+pub struct LegacyRoutes {
+    rules: Vec<(LegacyRouteRule, Option<Regex>)>,
+}
+
+impl LegacyRoutes {
+    /// Returns the legacy routes of the application. They are loaded (see [`LegacyRoutes::load`])
+    /// on the first call, which happens at startup.
+    pub fn get() -> Result<&'static Self, LegacyRoutesError> {
+        LEGACY_ROUTES.get_or_try_init(Self::load)
+    }
+
+    /// Loads the legacy routes from the JSON file `LEGACY_ROUTES_FILE` points to, or the built-in
+    /// defaults otherwise.
+    pub fn load() -> Result<Self, LegacyRoutesError> {
+        match env::var("LEGACY_ROUTES_FILE") {
+            Ok(file) => {
+                let config = fs::read_to_string(file)
+                    .map_err(|inner| LegacyRoutesError::IoError { inner })?;
+                Self::from_json(&config)
+            }
+            Err(_) => Self::from_json(DEFAULT_LEGACY_ROUTES),
+        }
+    }
+
+    pub fn from_json(config: &str) -> Result<Self, LegacyRoutesError> {
+        let rules: Vec<LegacyRouteRule> = serde_json::from_str(config)
+            .map_err(|inner| LegacyRoutesError::InvalidConfiguration { inner })?;
+        Self::new(rules)
+    }
+
+    pub fn new(rules: Vec<LegacyRouteRule>) -> Result<Self, LegacyRoutesError> {
+        let rules = rules
+            .into_iter()
+            .map(|rule| {
+                let regex = match rule.rule_type {
+                    LegacyRouteRuleType::Regex => {
+                        Some(Regex::new(&rule.value).map_err(|inner| {
+                            LegacyRoutesError::InvalidRegex {
+                                value: rule.value.clone(),
+                                inner,
+                            }
+                        })?)
+                    }
+                    _ => None,
+                };
+                Ok((rule, regex))
+            })
+            .collect::<Result<_, LegacyRoutesError>>()?;
+        Ok(LegacyRoutes { rules })
+    }
+
+    /// Returns the first rule matching the given path (without leading slash) in the instance.
+    pub fn find_match(&self, path: &str, instance: &Instance) -> Option<&LegacyRouteRule> {
+        self.rules
+            .iter()
+            .find(|(rule, regex)| {
+                if let Some(rule_instance) = &rule.instance {
+                    if rule_instance != instance {
+                        return false;
+                    }
+                }
+                match rule.rule_type {
+                    LegacyRouteRuleType::Exact => path == rule.value,
+                    LegacyRouteRuleType::Prefix => path.starts_with(&rule.value),
+                    LegacyRouteRuleType::Regex => regex.as_ref().unwrap().is_match(path),
+                }
+            })
+            .map(|(rule, _)| rule)
+    }
+
+    pub fn is_match(&self, path: &str, instance: &Instance) -> bool {
+        self.find_match(path, instance).is_some()
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LegacyRouteMatch {
+    pub instance: Instance,
+    pub path: String,
+    pub rule: Option<LegacyRouteRule>,
+}
+
+impl LegacyRouteMatch {
+    pub fn fetch(path: &str, instance: Instance) -> Result<Self, LegacyRoutesError> {
+        let path = path.trim_start_matches('/');
+        let rule = LegacyRoutes::get()?.find_match(path, &instance).cloned();
+        Ok(LegacyRouteMatch {
+            instance,
+            path: path.to_string(),
+            rule,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LegacyRouteRuleType, LegacyRoutes, DEFAULT_LEGACY_ROUTES};
+    use crate::instance::Instance;
+
+    #[test]
+    fn default_legacy_routes() {
+        let routes = LegacyRoutes::from_json(DEFAULT_LEGACY_ROUTES).unwrap();
+
+        assert!(routes.is_match("", &Instance::De));
+        assert!(routes.is_match("backend", &Instance::De));
+        assert!(routes.is_match("entity/view/1855", &Instance::En));
+        assert!(!routes.is_match("backend/foo", &Instance::De));
+        assert!(!routes.is_match("mathe/1855/title", &Instance::De));
+    }
+
+    #[test]
+    fn legacy_routes_with_regex_and_instance() {
+        let routes = LegacyRoutes::from_json(
+            r#"[
+                { "type": "regex", "value": "^archiv/\\d+$" },
+                { "type": "exact", "value": "spenden", "instance": "de" }
+            ]"#,
+        )
+        .unwrap();
+
+        let rule = routes.find_match("archiv/2018", &Instance::En).unwrap();
+        assert!(matches!(rule.rule_type, LegacyRouteRuleType::Regex));
+        assert!(!routes.is_match("archiv/foo", &Instance::En));
+        assert!(routes.is_match("spenden", &Instance::De));
+        assert!(!routes.is_match("spenden", &Instance::En));
+    }
+
+    #[test]
+    fn legacy_routes_with_invalid_regex() {
+        assert!(LegacyRoutes::from_json(r#"[{ "type": "regex", "value": "(" }]"#).is_err());
+    }
+}
@@ -2,15 +2,23 @@ use actix_web::HttpResponse;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
-use super::model::{Alias, AliasError};
+use super::bulk::{PathAliases, UuidAliases};
+use super::legacy_routes::LegacyRouteMatch;
+use super::model::{Alias, AliasCreateError, AliasCreatePayload, AliasError, AliasesByUuid};
 use crate::database::Connection;
 use crate::instance::Instance;
 use crate::message::MessageResponder;
+use crate::uuid::User;
 
 #[derive(Deserialize, Serialize)]
 #[serde(tag = "type", content = "payload")]
 pub enum AliasMessage {
     AliasQuery(AliasQuery),
+    AliasesByUuidQuery(AliasesByUuidQuery),
+    AliasCreateMutation(AliasCreateMutation),
+    LegacyRouteQuery(LegacyRouteQuery),
+    AliasesQuery(AliasesQuery),
+    UuidAliasesQuery(UuidAliasesQuery),
 }
 
 #[async_trait]
@@ -18,6 +26,11 @@ impl MessageResponder for AliasMessage {
     async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
         match self {
             AliasMessage::AliasQuery(message) => message.handle(connection).await,
+            AliasMessage::AliasesByUuidQuery(message) => message.handle(connection).await,
+            AliasMessage::AliasCreateMutation(message) => message.handle(connection).await,
+            AliasMessage::LegacyRouteQuery(message) => message.handle(connection).await,
+            AliasMessage::AliasesQuery(message) => message.handle(connection).await,
+            AliasMessage::UuidAliasesQuery(message) => message.handle(connection).await,
         }
     }
 }
@@ -27,6 +40,8 @@ impl MessageResponder for AliasMessage {
 pub struct AliasQuery {
     pub instance: Instance,
     pub path: String,
+    #[serde(default)]
+    pub include_uuid: bool,
 }
 
 #[async_trait]
@@ -41,9 +56,17 @@ impl MessageResponder for AliasQuery {
             }
         };
         match alias {
-            Ok(data) => HttpResponse::Ok()
-                .content_type("application/json; charset=utf-8")
-                .json(data),
+            Ok(mut data) => {
+                if !self.include_uuid {
+                    data.uuid = None;
+                }
+                if let Some(uuid) = data.uuid.as_mut() {
+                    User::strip_private_fields(uuid);
+                }
+                HttpResponse::Ok()
+                    .content_type("application/json; charset=utf-8")
+                    .json(data)
+            }
             Err(e) => {
                 println!("/alias/{:?}/{}: {:?}", self.instance, self.path, e);
                 match e {
@@ -51,6 +74,9 @@ impl MessageResponder for AliasQuery {
                         HttpResponse::InternalServerError().finish()
                     }
                     AliasError::InvalidInstance => HttpResponse::InternalServerError().finish(),
+                    AliasError::InvalidLegacyRoutes { .. } => {
+                        HttpResponse::InternalServerError().finish()
+                    }
                     AliasError::LegacyRoute => HttpResponse::NotFound().json(None::<String>),
                     AliasError::NotFound => HttpResponse::NotFound().json(None::<String>),
                 }
@@ -58,3 +84,152 @@ impl MessageResponder for AliasQuery {
         }
     }
 }
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AliasesByUuidQuery {
+    pub id: i32,
+}
+
+#[async_trait]
+impl MessageResponder for AliasesByUuidQuery {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let aliases = match connection {
+            Connection::Pool(pool) => AliasesByUuid::fetch(self.id, pool).await,
+            Connection::Transaction(transaction) => {
+                AliasesByUuid::fetch_via_transaction(self.id, transaction).await
+            }
+        };
+        match aliases {
+            Ok(data) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(data),
+            Err(e) => {
+                println!("/aliases/{}: {:?}", self.id, e);
+                HttpResponse::InternalServerError().finish()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AliasCreateMutation {
+    pub instance: Instance,
+    pub path: String,
+    pub id: i32,
+}
+
+#[async_trait]
+impl MessageResponder for AliasCreateMutation {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let payload = AliasCreatePayload {
+            instance: self.instance.clone(),
+            path: self.path.clone(),
+            id: self.id,
+        };
+        let alias = match connection {
+            Connection::Pool(pool) => Alias::create(payload, pool).await,
+            Connection::Transaction(transaction) => Alias::create(payload, transaction).await,
+        };
+        match alias {
+            Ok(data) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(data),
+            Err(e) => {
+                println!("{:?}: {:?}", self, e);
+                match e {
+                    AliasCreateError::DatabaseError { .. } => {
+                        HttpResponse::InternalServerError().finish()
+                    }
+                    AliasCreateError::InvalidLegacyRoutes { .. } => {
+                        HttpResponse::InternalServerError().finish()
+                    }
+                    AliasCreateError::InvalidPath
+                    | AliasCreateError::Collision { .. }
+                    | AliasCreateError::UnsupportedUuid => HttpResponse::BadRequest()
+                        .json(serde_json::json!({ "reason": e.to_string() })),
+                    AliasCreateError::UuidNotFound => HttpResponse::NotFound().json(None::<String>),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LegacyRouteQuery {
+    pub instance: Instance,
+    pub path: String,
+}
+
+#[async_trait]
+impl MessageResponder for LegacyRouteQuery {
+    async fn handle(&self, _connection: Connection<'_, '_>) -> HttpResponse {
+        match LegacyRouteMatch::fetch(self.path.as_str(), self.instance.clone()) {
+            Ok(data) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(data),
+            Err(e) => {
+                println!("/legacy-route/{:?}/{}: {:?}", self.instance, self.path, e);
+                HttpResponse::InternalServerError().finish()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AliasesQuery {
+    pub instance: Instance,
+    pub paths: Vec<String>,
+}
+
+#[async_trait]
+impl MessageResponder for AliasesQuery {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let instance = self.instance.clone();
+        let aliases = match connection {
+            Connection::Pool(pool) => PathAliases::fetch(&self.paths, instance, pool).await,
+            Connection::Transaction(transaction) => {
+                PathAliases::fetch_via_transaction(&self.paths, instance, transaction).await
+            }
+        };
+        match aliases {
+            Ok(data) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(data),
+            Err(e) => {
+                println!("{:?}: {:?}", self, e);
+                HttpResponse::InternalServerError().finish()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UuidAliasesQuery {
+    pub ids: Vec<i32>,
+}
+
+#[async_trait]
+impl MessageResponder for UuidAliasesQuery {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let aliases = match connection {
+            Connection::Pool(pool) => UuidAliases::fetch(&self.ids, pool).await,
+            Connection::Transaction(transaction) => {
+                UuidAliases::fetch_via_transaction(&self.ids, transaction).await
+            }
+        };
+        match aliases {
+            Ok(data) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(data),
+            Err(e) => {
+                println!("{:?}: {:?}", self, e);
+                HttpResponse::InternalServerError().finish()
+            }
+        }
+    }
+}
@@ -1,7 +1,11 @@
+pub use bulk::*;
+pub use legacy_routes::*;
 pub use messages::AliasMessage;
 pub use model::*;
 pub use routes::init;
 
+mod bulk;
+mod legacy_routes;
 mod messages;
 mod model;
 mod routes;
@@ -1,11 +1,13 @@
 use regex::Regex;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use sqlx::MySqlPool;
 use thiserror::Error;
 
+use super::legacy_routes::{LegacyRoutes, LegacyRoutesError};
 use crate::database::Executor;
+use crate::datetime::DateTime;
 use crate::instance::Instance;
-use crate::uuid::{Uuid, UuidError, UuidFetcher};
+use crate::uuid::{User, Uuid, UuidError, UuidFetcher};
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -13,6 +15,14 @@ pub struct Alias {
     pub id: i32,
     pub instance: Instance,
     pub path: String,
+    /// Whether the requested path is the canonical path of the UUID. Otherwise, clients should
+    /// redirect to `redirect_to`.
+    pub is_canonical: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redirect_to: Option<String>,
+    /// The resolved UUID. `AliasQuery` only embeds it if `includeUuid` is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<Uuid>,
 }
 
 #[derive(Error, Debug)]
@@ -23,6 +33,8 @@ pub enum AliasError {
     InvalidInstance,
     #[error("Alias is a legacy route.")]
     LegacyRoute,
+    #[error("Alias cannot be fetched because the legacy routes are invalid: {inner:?}.")]
+    InvalidLegacyRoutes { inner: LegacyRoutesError },
     #[error("Alias cannot be fetched because it does not exist.")]
     NotFound,
 }
@@ -33,6 +45,26 @@ impl From<sqlx::Error> for AliasError {
     }
 }
 
+impl From<LegacyRoutesError> for AliasError {
+    fn from(inner: LegacyRoutesError) -> Self {
+        AliasError::InvalidLegacyRoutes { inner }
+    }
+}
+
+impl From<UuidError> for AliasError {
+    fn from(error: UuidError) -> Self {
+        match error {
+            UuidError::DatabaseError { inner } => AliasError::DatabaseError { inner },
+            UuidError::InvalidInstance => AliasError::InvalidInstance,
+            UuidError::UnsupportedDiscriminator { .. } => AliasError::NotFound,
+            UuidError::UnsupportedEntityType { .. } => AliasError::NotFound,
+            UuidError::UnsupportedEntityRevisionType { .. } => AliasError::NotFound,
+            UuidError::EntityMissingRequiredParent => AliasError::NotFound,
+            UuidError::NotFound => AliasError::NotFound,
+        }
+    }
+}
+
 impl Alias {
     pub async fn fetch(
         path: &str,
@@ -50,54 +82,11 @@ impl Alias {
     where
         E: Executor<'a>,
     {
-        if path == "backend"
-            || path == "debugger"
-            || path == "horizon"
-            || path.starts_with("horizon/")
-            || path.starts_with("api/")
-            || path.is_empty()
-            || path == "application"
-            || path.starts_with("application/")
-            || path.starts_with("attachment/file/")
-            || path.starts_with("attachment/upload")
-            || path.starts_with("auth/")
-            || path.starts_with("authorization/")
-            || path == "blog"
-            || path.starts_with("blog/view-all/")
-            || path.starts_with("blog/view/")
-            || path.starts_with("blog/post/")
-            || path.starts_with("discussion/")
-            || path.starts_with("discussions/")
-            || path.starts_with("entities/")
-            || path.starts_with("entity/")
-            || path.starts_with("event/")
-            || path.starts_with("flag/")
-            || path.starts_with("license/")
-            || path.starts_with("navigation/")
-            || path.starts_with("meta/")
-            || path.starts_with("ref/")
-            || path.starts_with("sitemap/")
-            || path.starts_with("notification/")
-            || path.starts_with("subscribe/")
-            || path.starts_with("unsubscribe/")
-            || path.starts_with("subscription/")
-            || path.starts_with("subscriptions/")
-            || path == "pages"
-            || path.starts_with("page/")
-            || path.starts_with("related_content/")
-            || path == "search"
-            || path == "session/gc"
-            || path == "spenden"
-            || path.starts_with("taxonomies/")
-            || path.starts_with("taxonomy/")
-            || path == "users"
-            || path == "user/me"
-            || path == "user/public"
-            || path == "user/register"
-            || path == "user/settings"
-            || path.starts_with("user/remove/")
-            || path.starts_with("uuid/")
-        {
+        let compare_re =
+            Regex::new(r"^entity/repository/compare/(?P<entity_id>\d+)/(?P<id>\d+)$").unwrap();
+        let compare_captures = compare_re.captures(&path);
+
+        if compare_captures.is_none() && LegacyRoutes::get()?.is_match(path, &instance) {
             return Err(AliasError::LegacyRoute);
         }
 
@@ -105,29 +94,57 @@ impl Alias {
 
         let mut transaction = executor.begin().await?;
 
-        let id: i32 = match re.captures(&path) {
-            Some(captures) => {
-                let username = captures.name("username").unwrap().as_str();
+        let id: i32 = match (compare_captures, re.captures(&path)) {
+            (Some(captures), _) => {
+                let entity_id: i32 = captures
+                    .name("entity_id")
+                    .unwrap()
+                    .as_str()
+                    .parse()
+                    .map_err(|_| AliasError::NotFound)?;
+                let id: i32 = captures
+                    .name("id")
+                    .unwrap()
+                    .as_str()
+                    .parse()
+                    .map_err(|_| AliasError::NotFound)?;
+                // The revision needs to belong to the entity of the path.
                 sqlx::query!(
-                    r#"
-                        SELECT id
-                            FROM user
-                            WHERE username = ?
-                    "#,
-                    username
+                    r#"SELECT id FROM entity_revision WHERE id = ? AND repository_id = ?"#,
+                    id,
+                    entity_id
                 )
-                .fetch_one(&mut transaction)
-                .await
-                .map_err(|error| match error {
-                    sqlx::Error::RowNotFound => AliasError::NotFound,
-                    error => error.into(),
-                })
-                .map(|user| user.id as i32)?
+                .fetch_optional(&mut transaction)
+                .await?
+                .ok_or(AliasError::NotFound)?;
+                id
+            }
+            (_, Some(captures)) => {
+                let username = captures.name("username").unwrap().as_str();
+                // Legacy profile links may use the numeric id of the user instead of the username.
+                let user_id = match username.parse::<i32>() {
+                    Ok(user_id) => sqlx::query!(r#"SELECT id FROM user WHERE id = ?"#, user_id)
+                        .fetch_optional(&mut transaction)
+                        .await?
+                        .map(|user| user.id as i32),
+                    Err(_) => None,
+                };
+                match user_id {
+                    Some(user_id) => user_id,
+                    None => User::fetch_id_by_username(username, &mut transaction)
+                        .await?
+                        .ok_or(AliasError::NotFound)?,
+                }
             }
             _ => {
                 let re = Regex::new(r"^(?P<subject>[^/]+/)?(?P<id>\d+)/(?P<title>[^/]*)$").unwrap();
                 match re.captures(&path) {
-                    Some(captures) => captures.name("id").unwrap().as_str().parse().unwrap(),
+                    Some(captures) => captures
+                        .name("id")
+                        .unwrap()
+                        .as_str()
+                        .parse()
+                        .map_err(|_| AliasError::NotFound)?,
                     _ => sqlx::query!(
                         r#"
                             SELECT a.uuid_id FROM url_alias a
@@ -149,24 +166,394 @@ impl Alias {
             }
         };
 
-        let uuid = Uuid::fetch_via_transaction(id, &mut transaction)
-            .await
-            .map_err(|error| match error {
-                UuidError::DatabaseError { inner } => AliasError::DatabaseError { inner },
-                UuidError::InvalidInstance => AliasError::InvalidInstance,
-                UuidError::UnsupportedDiscriminator { .. } => AliasError::NotFound,
-                UuidError::UnsupportedEntityType { .. } => AliasError::NotFound,
-                UuidError::UnsupportedEntityRevisionType { .. } => AliasError::NotFound,
-                UuidError::EntityMissingRequiredParent => AliasError::NotFound,
-                UuidError::NotFound => AliasError::NotFound,
-            })?;
+        let uuid = Uuid::fetch_via_transaction(id, &mut transaction).await?;
 
         transaction.commit().await?;
 
+        // Every path that resolves to a UUID but differs from its current alias (e.g. a stored
+        // alias or an outdated title) redirects to the canonical path.
+        let canonical_path = uuid.get_alias();
+        let is_canonical = canonical_path == format!("/{}", path);
+        let redirect_to = if is_canonical {
+            None
+        } else {
+            Some(canonical_path.clone())
+        };
+
         Ok(Alias {
             id,
             instance,
-            path: uuid.get_alias(),
+            path: canonical_path,
+            is_canonical,
+            redirect_to,
+            uuid: Some(uuid),
         })
     }
 }
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AliasesByUuid {
+    pub id: i32,
+    pub aliases: Vec<AliasHistoryEntry>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AliasHistoryEntry {
+    pub instance: Instance,
+    pub path: String,
+    pub date: DateTime,
+}
+
+impl AliasesByUuid {
+    pub async fn fetch(id: i32, pool: &MySqlPool) -> Result<Self, AliasError> {
+        Self::fetch_via_transaction(id, pool).await
+    }
+
+    pub async fn fetch_via_transaction<'a, E>(id: i32, executor: E) -> Result<Self, AliasError>
+    where
+        E: Executor<'a>,
+    {
+        let aliases = sqlx::query!(
+            r#"
+                SELECT a.alias, a.timestamp, i.subdomain
+                    FROM url_alias a
+                    JOIN instance i ON i.id = a.instance_id
+                    WHERE a.uuid_id = ?
+                    ORDER BY a.timestamp DESC, a.id DESC
+            "#,
+            id
+        )
+        .fetch_all(executor)
+        .await?;
+
+        let aliases = aliases
+            .into_iter()
+            .map(|alias| {
+                Ok(AliasHistoryEntry {
+                    instance: alias
+                        .subdomain
+                        .parse()
+                        .map_err(|_| AliasError::InvalidInstance)?,
+                    path: format!("/{}", alias.alias),
+                    date: alias.timestamp.into(),
+                })
+            })
+            .collect::<Result<_, AliasError>>()?;
+
+        Ok(AliasesByUuid { id, aliases })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AliasCreatePayload {
+    pub instance: Instance,
+    pub path: String,
+    pub id: i32,
+}
+
+#[derive(Error, Debug)]
+pub enum AliasCreateError {
+    #[error("Alias cannot be created because of a database error: {inner:?}.")]
+    DatabaseError { inner: sqlx::Error },
+    #[error("Alias cannot be created because its path is invalid.")]
+    InvalidPath,
+    #[error("Alias cannot be created because the legacy routes are invalid: {inner:?}.")]
+    InvalidLegacyRoutes { inner: LegacyRoutesError },
+    #[error("Alias cannot be created because it is already used by UUID {id:?}.")]
+    Collision { id: i32 },
+    #[error("Alias cannot be created because the UUID does not support aliases.")]
+    UnsupportedUuid,
+    #[error("Alias cannot be created because the UUID does not exist.")]
+    UuidNotFound,
+}
+
+impl From<sqlx::Error> for AliasCreateError {
+    fn from(inner: sqlx::Error) -> Self {
+        AliasCreateError::DatabaseError { inner }
+    }
+}
+
+impl From<LegacyRoutesError> for AliasCreateError {
+    fn from(inner: LegacyRoutesError) -> Self {
+        AliasCreateError::InvalidLegacyRoutes { inner }
+    }
+}
+
+impl Alias {
+    /// Creates a custom alias for the given UUID.
+    ///
+    /// Aliases are never deleted: when a UUID gets a new alias, its former aliases still resolve
+    /// (see [`Alias::fetch`]). If the alias already exists for the same UUID, it becomes its most
+    /// recent one again.
+    pub async fn create<'a, E>(
+        payload: AliasCreatePayload,
+        executor: E,
+    ) -> Result<Alias, AliasCreateError>
+    where
+        E: Executor<'a>,
+    {
+        let path = payload.path.trim_matches('/');
+
+        let id_re = Regex::new(r"^(?P<subject>[^/]+/)?(?P<id>\d+)/(?P<title>[^/]*)$").unwrap();
+        let path_re = Regex::new(r"^[^\s?#]+$").unwrap();
+        if LegacyRoutes::get()?.is_match(path, &payload.instance)
+            || id_re.is_match(path)
+            || path.starts_with("user/profile/")
+            || !path_re.is_match(path)
+            || path.contains("//")
+        {
+            return Err(AliasCreateError::InvalidPath);
+        }
+
+        let mut transaction = executor.begin().await?;
+
+        let uuid = sqlx::query!(r#"SELECT discriminator FROM uuid WHERE id = ?"#, payload.id)
+            .fetch_one(&mut transaction)
+            .await
+            .map_err(|error| match error {
+                sqlx::Error::RowNotFound => AliasCreateError::UuidNotFound,
+                error => error.into(),
+            })?;
+        let source = match uuid.discriminator.as_str() {
+            "blogPost" => format!("/blog/post/view/{}", payload.id),
+            "entity" => format!("/entity/view/{}", payload.id),
+            "page" => format!("/page/view/{}", payload.id),
+            "taxonomyTerm" => format!("/taxonomy/term/get/{}", payload.id),
+            _ => return Err(AliasCreateError::UnsupportedUuid),
+        };
+
+        let existing = sqlx::query!(
+            r#"
+                SELECT a.id, a.uuid_id FROM url_alias a
+                    JOIN instance i on i.id = a.instance_id
+                    WHERE i.subdomain = ? AND a.alias = ?
+                    ORDER BY a.timestamp DESC
+            "#,
+            payload.instance,
+            path
+        )
+        .fetch_all(&mut transaction)
+        .await?;
+
+        match existing.first() {
+            Some(alias) if alias.uuid_id as i32 != payload.id => {
+                return Err(AliasCreateError::Collision {
+                    id: alias.uuid_id as i32,
+                });
+            }
+            Some(alias) => {
+                sqlx::query!(
+                    r#"UPDATE url_alias SET timestamp = ? WHERE id = ?"#,
+                    DateTime::now(),
+                    alias.id
+                )
+                .execute(&mut transaction)
+                .await?;
+            }
+            None => {
+                sqlx::query!(
+                    r#"
+                        INSERT INTO url_alias (instance_id, uuid_id, source, alias, timestamp)
+                            SELECT i.id, ?, ?, ?, ?
+                                FROM instance i
+                                WHERE i.subdomain = ?
+                    "#,
+                    payload.id,
+                    source,
+                    path,
+                    DateTime::now(),
+                    payload.instance
+                )
+                .execute(&mut transaction)
+                .await?;
+            }
+        }
+
+        let alias = Self::fetch_via_transaction(path, payload.instance, &mut transaction)
+            .await
+            .map_err(|error| match error {
+                AliasError::DatabaseError { inner } => AliasCreateError::DatabaseError { inner },
+                _ => AliasCreateError::UnsupportedUuid,
+            })?;
+
+        transaction.commit().await?;
+
+        Ok(alias)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Alias, AliasCreateError, AliasCreatePayload, AliasError, AliasesByUuid};
+    use crate::create_database_pool;
+    use crate::instance::Instance;
+    use crate::uuid::{Uuid, UuidFetcher};
+
+    #[actix_rt::test]
+    async fn alias_with_outdated_title_redirects() {
+        let pool = create_database_pool().await.unwrap();
+
+        let alias = Alias::fetch("mathe/1855/veralteter-titel", Instance::De, &pool)
+            .await
+            .unwrap();
+
+        assert_eq!(alias.id, 1855);
+        assert!(!alias.is_canonical);
+        assert_eq!(alias.redirect_to, Some(alias.path.clone()));
+
+        let canonical_alias = Alias::fetch(&alias.path[1..], Instance::De, &pool)
+            .await
+            .unwrap();
+
+        assert!(canonical_alias.is_canonical);
+        assert_eq!(canonical_alias.redirect_to, None);
+    }
+
+    #[actix_rt::test]
+    async fn alias_with_numeric_user_profile() {
+        let pool = create_database_pool().await.unwrap();
+
+        let alias = Alias::fetch("user/profile/1", Instance::De, &pool)
+            .await
+            .unwrap();
+
+        assert_eq!(alias.id, 1);
+        assert!(!alias.is_canonical);
+        assert!(alias.path.starts_with("/user/1/"));
+    }
+
+    #[actix_rt::test]
+    async fn alias_with_entity_revision_compare_path() {
+        let pool = create_database_pool().await.unwrap();
+        let revision = sqlx::query!(
+            r#"SELECT id FROM entity_revision WHERE repository_id = ? LIMIT 1"#,
+            1855
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let path = format!("entity/repository/compare/1855/{}", revision.id);
+
+        let alias = Alias::fetch(&path, Instance::De, &pool).await.unwrap();
+
+        assert_eq!(alias.id, revision.id as i32);
+        assert!(alias.is_canonical);
+    }
+
+    #[actix_rt::test]
+    async fn alias_with_invalid_entity_revision_compare_path() {
+        let pool = create_database_pool().await.unwrap();
+        let revision = sqlx::query!(
+            r#"SELECT id FROM entity_revision WHERE repository_id != ? LIMIT 1"#,
+            1855
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        for path in [
+            format!("entity/repository/compare/1855/{}", revision.id),
+            "entity/repository/compare/1855/99999999999".to_string(),
+        ]
+        .iter()
+        {
+            let result = Alias::fetch(path, Instance::De, &pool).await;
+
+            assert!(matches!(result, Err(AliasError::NotFound)));
+        }
+    }
+
+    #[actix_rt::test]
+    async fn create_alias() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        let alias = Alias::create(
+            AliasCreatePayload {
+                instance: Instance::De,
+                path: "/mathe/eine-eigene-adresse".to_string(),
+                id: 1855,
+            },
+            &mut transaction,
+        )
+        .await
+        .unwrap();
+        assert_eq!(alias.id, 1855);
+
+        // Stored aliases resolve to their UUID but redirect to the generated alias.
+        let uuid = Uuid::fetch_via_transaction(1855, &mut transaction)
+            .await
+            .unwrap();
+        assert_eq!(alias.path, uuid.get_alias());
+        assert!(!alias.is_canonical);
+        assert_eq!(alias.redirect_to, Some(uuid.get_alias()));
+
+        // Verify that the alias is now the most recent one of the UUID.
+        let aliases = AliasesByUuid::fetch_via_transaction(1855, &mut transaction)
+            .await
+            .unwrap();
+        assert_eq!(aliases.aliases[0].path, "/mathe/eine-eigene-adresse");
+    }
+
+    #[actix_rt::test]
+    async fn create_alias_collision() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        Alias::create(
+            AliasCreatePayload {
+                instance: Instance::De,
+                path: "mathe/eine-eigene-adresse".to_string(),
+                id: 1855,
+            },
+            &mut transaction,
+        )
+        .await
+        .unwrap();
+
+        let result = Alias::create(
+            AliasCreatePayload {
+                instance: Instance::De,
+                path: "mathe/eine-eigene-adresse".to_string(),
+                id: 1565,
+            },
+            &mut transaction,
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(AliasCreateError::Collision { id: 1855 })
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn create_alias_invalid_path() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        for path in [
+            "mathe/1855/title",
+            "entity/view/1855",
+            "",
+            "mit leerzeichen",
+        ]
+        .iter()
+        {
+            let result = Alias::create(
+                AliasCreatePayload {
+                    instance: Instance::De,
+                    path: path.to_string(),
+                    id: 1855,
+                },
+                &mut transaction,
+            )
+            .await;
+
+            assert!(matches!(result, Err(AliasCreateError::InvalidPath)));
+        }
+    }
+}
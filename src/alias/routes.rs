@@ -1,22 +1,57 @@
 use actix_web::{get, web, Responder};
+use serde::Deserialize;
 use sqlx::MySqlPool;
 
-use super::messages::AliasQuery;
+use super::messages::{AliasQuery, AliasesByUuidQuery, LegacyRouteQuery};
 use crate::database::Connection;
 use crate::instance::Instance;
 use crate::message::MessageResponder;
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AliasQueryParams {
+    #[serde(default)]
+    include_uuid: bool,
+}
+
 #[get("/alias/{instance}/{path:.*}")]
 async fn alias(
+    params: web::Path<(Instance, String)>,
+    query: web::Query<AliasQueryParams>,
+    db_pool: web::Data<MySqlPool>,
+) -> impl Responder {
+    let (instance, path) = params.into_inner();
+    let message = AliasQuery {
+        instance,
+        path,
+        include_uuid: query.include_uuid,
+    };
+    let connection = Connection::Pool(db_pool.get_ref());
+    message.handle(connection).await
+}
+
+#[get("/aliases/{id}")]
+async fn aliases(id: web::Path<i32>, db_pool: web::Data<MySqlPool>) -> impl Responder {
+    let message = AliasesByUuidQuery {
+        id: id.into_inner(),
+    };
+    let connection = Connection::Pool(db_pool.get_ref());
+    message.handle(connection).await
+}
+
+#[get("/legacy-route/{instance}/{path:.*}")]
+async fn legacy_route(
     params: web::Path<(Instance, String)>,
     db_pool: web::Data<MySqlPool>,
 ) -> impl Responder {
     let (instance, path) = params.into_inner();
-    let message = AliasQuery { instance, path };
+    let message = LegacyRouteQuery { instance, path };
     let connection = Connection::Pool(db_pool.get_ref());
     message.handle(connection).await
 }
 
 pub fn init(cfg: &mut web::ServiceConfig) {
     cfg.service(alias);
+    cfg.service(aliases);
+    cfg.service(legacy_route);
 }
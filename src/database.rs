@@ -46,6 +46,57 @@ pub trait Executor<'a>: sqlx::Executor<'a, Database = sqlx::MySql> + Acquire<'a>
 
 impl<'a, E> Executor<'a> for E where E: sqlx::Executor<'a, Database = sqlx::MySql> + Acquire<'a> {}
 
+/// Returns a comma-separated list of `count` placeholders to be used in an `IN (...)` clause.
+///
+/// `sqlx::query!` cannot bind a list of values in MySQL, so queries that take a variable number of
+/// ids need to be built at runtime with `sqlx::query` / `sqlx::query_as`:
+///
+/// ```rust
+/// use serlo_org_database_layer::database::{placeholders, Executor};
+///
+/// async fn fetch_trashed<'a, E>(ids: &[i32], executor: E) -> Result<Vec<(i64,)>, sqlx::Error>
+/// where
+///     E: Executor<'a>,
+/// {
+///     let query = format!(
+///         "SELECT id FROM uuid WHERE trashed = 1 AND id IN ({})",
+///         placeholders(ids.len())
+///     );
+///     let mut query = sqlx::query_as(&query);
+///     for id in ids {
+///         query = query.bind(id);
+///     }
+///     query.fetch_all(executor).await
+/// }
+/// ```
+///
+/// Note: callers need to handle an empty list themselves since `IN ()` is invalid SQL.
+pub fn placeholders(count: usize) -> String {
+    vec!["?"; count].join(", ")
+}
+
+/// Runs a query containing one `IN ({})` clause for the given ids (see [`placeholders`]). An empty
+/// list of ids yields no rows without running the query.
+pub async fn fetch_all_by_ids<'a, E, T>(
+    query: &str,
+    ids: &[i32],
+    executor: E,
+) -> Result<Vec<T>, sqlx::Error>
+where
+    E: Executor<'a>,
+    T: for<'r> sqlx::FromRow<'r, sqlx::mysql::MySqlRow> + Send + Unpin,
+{
+    if ids.is_empty() {
+        return Ok(vec![]);
+    }
+    let query = query.replace("{}", &placeholders(ids.len()));
+    let mut query = sqlx::query_as::<_, T>(&query);
+    for id in ids {
+        query = query.bind(id);
+    }
+    query.fetch_all(executor).await
+}
+
 #[derive(Debug)]
 pub enum Connection<'c, 'e>
 where
@@ -6,7 +6,7 @@ use std::fmt;
 
 use chrono::{Duration, TimeZone, Utc};
 use chrono_tz::Europe::Berlin;
-use serde::{Serialize, Serializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use sqlx::database::HasArguments;
 use sqlx::encode::IsNull;
 use sqlx::mysql::MySqlTypeInfo;
@@ -38,7 +38,7 @@ use sqlx::MySql;
 /// # Ok(())
 /// # }
 /// ```
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct DateTime(chrono::DateTime<Utc>);
 
 impl DateTime {
@@ -82,6 +82,26 @@ impl Serialize for DateTime {
     }
 }
 
+impl std::str::FromStr for DateTime {
+    type Err = chrono::ParseError;
+
+    /// Parses an RFC 3339 timestamp (as returned by `Display`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let datetime = chrono::DateTime::parse_from_rfc3339(s)?;
+        Ok(DateTime(datetime.with_timezone(&Utc)))
+    }
+}
+
+impl<'de> Deserialize<'de> for DateTime {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 impl sqlx::Type<MySql> for DateTime {
     fn type_info() -> MySqlTypeInfo {
         chrono::DateTime::type_info()
@@ -3,7 +3,12 @@ use std::convert::TryFrom;
 use serde::Serialize;
 
 use super::abstract_event::AbstractEvent;
-use super::EventError;
+use super::event_type::RawEventType;
+use super::{Event, EventError};
+use crate::database::Executor;
+use crate::datetime::DateTime;
+use crate::instance::Instance;
+use crate::notification::{Notifications, NotificationsError};
 
 #[derive(Debug, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -25,3 +30,94 @@ impl TryFrom<&AbstractEvent> for TaxonomyLinkEvent {
         })
     }
 }
+
+pub struct CreateTaxonomyLinkEventPayload {
+    raw_typename: RawEventType,
+    actor_id: i32,
+    parent_id: i32,
+    child_id: i32,
+    instance: Instance,
+}
+
+impl CreateTaxonomyLinkEventPayload {
+    pub fn new(parent_id: i32, child_id: i32, actor_id: i32, instance: Instance) -> Self {
+        CreateTaxonomyLinkEventPayload {
+            raw_typename: RawEventType::CreateTaxonomyLink,
+            actor_id,
+            parent_id,
+            child_id,
+            instance,
+        }
+    }
+
+    pub async fn save<'a, E>(&self, executor: E) -> Result<Event, EventError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        // insert event_log
+        sqlx::query!(
+            r#"
+                INSERT INTO event_log (actor_id, event_id, uuid_id, instance_id, date)
+                    SELECT ?, e.id, ?, i.id, ?
+                    FROM event e
+                    JOIN instance i
+                    WHERE e.name = ? AND i.subdomain = ?
+            "#,
+            self.actor_id,
+            self.parent_id,
+            DateTime::now(),
+            self.raw_typename,
+            self.instance
+        )
+        .execute(&mut transaction)
+        .await?;
+        let value = sqlx::query!(r#"SELECT LAST_INSERT_ID() as id"#)
+            .fetch_one(&mut transaction)
+            .await?;
+        let event_log_id = value.id as i32;
+
+        // insert event_parameter
+        sqlx::query!(
+            r#"
+                INSERT INTO event_parameter (log_id, name_id)
+                    SELECT ?, id
+                    FROM event_parameter_name
+                    WHERE name = ?
+            "#,
+            event_log_id,
+            "object"
+        )
+        .execute(&mut transaction)
+        .await?;
+        let value = sqlx::query!(r#"SELECT LAST_INSERT_ID() as id"#)
+            .fetch_one(&mut transaction)
+            .await?;
+        let parameter_id = value.id;
+
+        // insert event_parameter_uuid
+        sqlx::query!(
+            r#"
+                INSERT INTO event_parameter_uuid (uuid_id, event_parameter_id)
+                    VALUES (?, ?)
+            "#,
+            self.child_id,
+            parameter_id
+        )
+        .execute(&mut transaction)
+        .await?;
+
+        let event = Event::fetch_via_transaction(event_log_id, &mut transaction).await?;
+
+        Notifications::create_notifications(&event, &mut transaction)
+            .await
+            .map_err(|error| match error {
+                NotificationsError::DatabaseError { inner } => EventError::from(inner),
+            })?;
+
+        transaction.commit().await?;
+
+        Ok(event)
+    }
+}
@@ -10,6 +10,10 @@ use sqlx::pool::Pool;
 use sqlx::{MySql, MySqlPool};
 use thiserror::Error;
 
+use crate::alias::LegacyRoutesError;
+use crate::instance::Instance;
+use crate::slug::{SlugGenerator, SlugRules};
+
 pub mod alias;
 pub mod database;
 pub mod datetime;
@@ -21,29 +25,29 @@ pub mod message;
 pub mod navigation;
 pub mod notification;
 pub mod routes;
+pub mod sitemap;
+pub mod slug;
 pub mod subscription;
 pub mod thread;
 pub mod user;
 pub mod uuid;
 
-pub fn format_alias(prefix: Option<&str>, id: i32, suffix: Option<&str>) -> String {
+pub fn format_alias(
+    instance: Option<&Instance>,
+    prefix: Option<&str>,
+    id: i32,
+    suffix: Option<&str>,
+) -> String {
+    let slug_rules = SlugRules::for_instance(instance);
     let prefix = prefix
-        .map(|p| format!("/{}", slugify(p)))
+        .map(|p| format!("/{}", slug_rules.slugify(p)))
+        .unwrap_or_else(|| "".to_string());
+    let suffix = suffix
+        .map(|s| slug_rules.slugify(s))
         .unwrap_or_else(|| "".to_string());
-    let suffix = suffix.map(slugify).unwrap_or_else(|| "".to_string());
     format!("{}/{}/{}", prefix, id, suffix)
 }
 
-fn slugify(segment: &str) -> String {
-    let segment = Regex::new(r#"['"`=+*&^%$#@!<>?]"#)
-        .unwrap()
-        .replace_all(&segment, "");
-    let segment = Regex::new(r"[\[\]{}() ,;:/|\-]+")
-        .unwrap()
-        .replace_all(&segment, "-");
-    segment.to_lowercase().trim_matches('-').to_string()
-}
-
 pub fn configure_app<T, B>(app: App<T, B>, pool: MySqlPool) -> App<T, B>
 where
     B: MessageBody,
@@ -63,6 +67,7 @@ where
         .configure(license::init)
         .configure(navigation::init)
         .configure(notification::init)
+        .configure(sitemap::init)
         .configure(subscription::init)
         .configure(thread::init)
         .configure(user::init)
@@ -109,6 +114,8 @@ pub enum ApplicationError {
     DatabaseError { inner: sqlx::Error },
     #[error("Server error: {inner:?}.")]
     ServerError { inner: std::io::Error },
+    #[error("Legacy routes error: {inner:?}.")]
+    LegacyRoutesError { inner: LegacyRoutesError },
 }
 
 impl From<sqlx::Error> for ApplicationError {
@@ -117,15 +124,27 @@ impl From<sqlx::Error> for ApplicationError {
     }
 }
 
+impl From<LegacyRoutesError> for ApplicationError {
+    fn from(inner: LegacyRoutesError) -> Self {
+        ApplicationError::LegacyRoutesError { inner }
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::slugify;
+    use super::format_alias;
+    use crate::instance::Instance;
 
     #[test]
     fn format_alias_double_dash() {
         assert_eq!(
-            slugify("Flächen- und Volumenberechnung mit Integralen"),
-            "flächen-und-volumenberechnung-mit-integralen"
+            format_alias(
+                Some(&Instance::De),
+                None,
+                1,
+                Some("Flächen- und Volumenberechnung mit Integralen")
+            ),
+            "/1/flächen-und-volumenberechnung-mit-integralen"
         )
     }
 }
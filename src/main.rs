@@ -1,10 +1,12 @@
 use actix_web::{App, HttpServer, Result};
 
+use serlo_org_database_layer::alias::LegacyRoutes;
 use serlo_org_database_layer::{configure_app, create_database_pool, ApplicationError};
 
 #[actix_web::main]
 async fn main() -> Result<(), ApplicationError> {
     let pool = create_database_pool().await?;
+    LegacyRoutes::get()?;
 
     println!("🚀 Server ready: http://localhost:8080");
 
@@ -2,7 +2,11 @@ use actix_web::HttpResponse;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
-use super::model::{Navigation, NavigationError};
+use super::model::{
+    Navigation, NavigationDiagnostics, NavigationError, NavigationPage,
+    NavigationPageCreatePayload, NavigationPageError, NavigationPageMovePayload,
+    NavigationPageParameters, NavigationPageUpdatePayload, NavigationPath, NavigationPathError,
+};
 use crate::database::Connection;
 use crate::instance::Instance;
 use crate::message::MessageResponder;
@@ -11,6 +15,12 @@ use crate::message::MessageResponder;
 #[serde(tag = "type", content = "payload")]
 pub enum NavigationMessage {
     NavigationQuery(NavigationQuery),
+    NavigationDiagnosticsQuery(NavigationDiagnosticsQuery),
+    NavigationPathQuery(NavigationPathQuery),
+    NavigationPageCreateMutation(NavigationPageCreateMutation),
+    NavigationPageUpdateMutation(NavigationPageUpdateMutation),
+    NavigationPageMoveMutation(NavigationPageMoveMutation),
+    NavigationPageDeleteMutation(NavigationPageDeleteMutation),
 }
 
 #[async_trait]
@@ -18,6 +28,22 @@ impl MessageResponder for NavigationMessage {
     async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
         match self {
             NavigationMessage::NavigationQuery(message) => message.handle(connection).await,
+            NavigationMessage::NavigationDiagnosticsQuery(message) => {
+                message.handle(connection).await
+            }
+            NavigationMessage::NavigationPathQuery(message) => message.handle(connection).await,
+            NavigationMessage::NavigationPageCreateMutation(message) => {
+                message.handle(connection).await
+            }
+            NavigationMessage::NavigationPageUpdateMutation(message) => {
+                message.handle(connection).await
+            }
+            NavigationMessage::NavigationPageMoveMutation(message) => {
+                message.handle(connection).await
+            }
+            NavigationMessage::NavigationPageDeleteMutation(message) => {
+                message.handle(connection).await
+            }
         }
     }
 }
@@ -53,3 +79,220 @@ impl MessageResponder for NavigationQuery {
         }
     }
 }
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavigationDiagnosticsQuery {
+    pub instance: Instance,
+}
+
+#[async_trait]
+impl MessageResponder for NavigationDiagnosticsQuery {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let instance = self.instance.clone();
+        let diagnostics = match connection {
+            Connection::Pool(pool) => NavigationDiagnostics::fetch(instance, pool).await,
+            Connection::Transaction(transaction) => {
+                NavigationDiagnostics::fetch_via_transaction(instance, transaction).await
+            }
+        };
+        match diagnostics {
+            Ok(data) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(data),
+            Err(e) => {
+                println!("{:?}: {:?}", self, e);
+                match e {
+                    NavigationError::DatabaseError { .. } => {
+                        HttpResponse::InternalServerError().finish()
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavigationPathQuery {
+    pub instance: Instance,
+    pub id: i32,
+    #[serde(default)]
+    pub include_taxonomy_fallback: bool,
+}
+
+#[async_trait]
+impl MessageResponder for NavigationPathQuery {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let instance = self.instance.clone();
+        let navigation_path = match connection {
+            Connection::Pool(pool) => {
+                NavigationPath::fetch(instance, self.id, self.include_taxonomy_fallback, pool).await
+            }
+            Connection::Transaction(transaction) => {
+                NavigationPath::fetch_via_transaction(
+                    instance,
+                    self.id,
+                    self.include_taxonomy_fallback,
+                    transaction,
+                )
+                .await
+            }
+        };
+        match navigation_path {
+            Ok(data) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(data),
+            Err(e) => {
+                println!("{:?}: {:?}", self, e);
+                match e {
+                    NavigationPathError::DatabaseError { .. } => {
+                        HttpResponse::InternalServerError().finish()
+                    }
+                    NavigationPathError::NotFound => HttpResponse::NotFound().json(None::<String>),
+                }
+            }
+        }
+    }
+}
+
+fn navigation_page_error_response(error: NavigationPageError) -> HttpResponse {
+    match error {
+        NavigationPageError::DatabaseError { .. } => HttpResponse::InternalServerError().finish(),
+        NavigationPageError::InvalidParameters { .. }
+        | NavigationPageError::InvalidParent
+        | NavigationPageError::NavigationNotFound => {
+            HttpResponse::BadRequest().json(serde_json::json!({ "reason": error.to_string() }))
+        }
+        NavigationPageError::NotFound => HttpResponse::NotFound().json(None::<String>),
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavigationPageCreateMutation {
+    pub instance: Instance,
+    pub parent_id: Option<i32>,
+    pub position: Option<i32>,
+    #[serde(flatten)]
+    pub parameters: NavigationPageParameters,
+}
+
+#[async_trait]
+impl MessageResponder for NavigationPageCreateMutation {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let payload = NavigationPageCreatePayload {
+            instance: self.instance.clone(),
+            parent_id: self.parent_id,
+            position: self.position,
+            parameters: self.parameters.clone(),
+        };
+        let page = match connection {
+            Connection::Pool(pool) => NavigationPage::create(payload, pool).await,
+            Connection::Transaction(transaction) => {
+                NavigationPage::create(payload, transaction).await
+            }
+        };
+        match page {
+            Ok(data) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(data),
+            Err(e) => {
+                println!("{:?}: {:?}", self, e);
+                navigation_page_error_response(e)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavigationPageUpdateMutation {
+    pub id: i32,
+    #[serde(flatten)]
+    pub parameters: NavigationPageParameters,
+}
+
+#[async_trait]
+impl MessageResponder for NavigationPageUpdateMutation {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let payload = NavigationPageUpdatePayload {
+            id: self.id,
+            parameters: self.parameters.clone(),
+        };
+        let page = match connection {
+            Connection::Pool(pool) => NavigationPage::update(payload, pool).await,
+            Connection::Transaction(transaction) => {
+                NavigationPage::update(payload, transaction).await
+            }
+        };
+        match page {
+            Ok(data) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(data),
+            Err(e) => {
+                println!("{:?}: {:?}", self, e);
+                navigation_page_error_response(e)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavigationPageMoveMutation {
+    pub id: i32,
+    pub parent_id: Option<i32>,
+    pub position: Option<i32>,
+}
+
+#[async_trait]
+impl MessageResponder for NavigationPageMoveMutation {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let payload = NavigationPageMovePayload {
+            id: self.id,
+            parent_id: self.parent_id,
+            position: self.position,
+        };
+        let page = match connection {
+            Connection::Pool(pool) => NavigationPage::move_page(payload, pool).await,
+            Connection::Transaction(transaction) => {
+                NavigationPage::move_page(payload, transaction).await
+            }
+        };
+        match page {
+            Ok(data) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(data),
+            Err(e) => {
+                println!("{:?}: {:?}", self, e);
+                navigation_page_error_response(e)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavigationPageDeleteMutation {
+    pub id: i32,
+}
+
+#[async_trait]
+impl MessageResponder for NavigationPageDeleteMutation {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let result = match connection {
+            Connection::Pool(pool) => NavigationPage::delete(self.id, pool).await,
+            Connection::Transaction(transaction) => {
+                NavigationPage::delete(self.id, transaction).await
+            }
+        };
+        match result {
+            Ok(_) => HttpResponse::Ok().finish(),
+            Err(e) => {
+                println!("{:?}: {:?}", self, e);
+                navigation_page_error_response(e)
+            }
+        }
+    }
+}
@@ -1,4 +1,16 @@
 pub use self::navigation::{Navigation, NavigationError};
+pub use self::navigation_diagnostics::{
+    DroppedNavigationNode, InvalidUuidNavigationNode, InvalidUuidReason, NavigationDiagnostics,
+    NavigationNodeDropReason, NavigationPathEntry,
+};
+pub use self::navigation_page::{
+    NavigationPage, NavigationPageCreatePayload, NavigationPageError, NavigationPageMovePayload,
+    NavigationPageParameters, NavigationPageUpdatePayload,
+};
+pub use self::navigation_path::{NavigationPath, NavigationPathError};
 
 mod navigation;
 mod navigation_child;
+mod navigation_diagnostics;
+mod navigation_page;
+mod navigation_path;
@@ -29,49 +29,40 @@ impl From<sqlx::Error> for NavigationError {
     }
 }
 
-macro_rules! fetch_all_pages {
-    ($instance: expr, $executor: expr) => {
-        sqlx::query!(
-            r#"
-                    SELECT p.id
-                        FROM navigation_page p
-                        JOIN navigation_container c ON c.id = p.container_id
-                        JOIN instance i ON i.id = c.instance_id
-                        JOIN type t ON t.id = c.type_id
-                        WHERE i.subdomain = ? AND t.name = 'default' AND p.parent_id IS NULL
-                        ORDER BY p.position, p.id
-                "#,
-            $instance
-        )
-        .fetch_all($executor)
-    };
+impl From<RawNavigationChildError> for NavigationError {
+    fn from(error: RawNavigationChildError) -> Self {
+        match error {
+            RawNavigationChildError::DatabaseError { inner } => inner.into(),
+        }
+    }
 }
 
-macro_rules! handle_raw_navigation_child {
-    ($id: expr, $result: expr, $raw_navigation_children: expr, $stack: expr, $ids_dfs: expr) => {
-        match $result {
-            Ok(navigation_child) => {
-                $ids_dfs.push($id);
-                for id in navigation_child.children.iter() {
-                    $stack.push(*id);
-                }
-                $raw_navigation_children.insert($id, navigation_child);
-            }
-            Err(error) => {
-                return match error {
-                    RawNavigationChildError::DatabaseError { inner } => Err(inner.into()),
-                }
-            }
+/// Returns the ids of the pages reachable from the top-level pages in depth-first order.
+fn get_ids_dfs(
+    root_ids: &[i32],
+    raw_navigation_children: &HashMap<i32, RawNavigationChild>,
+) -> Vec<i32> {
+    let mut stack: Vec<i32> = root_ids.to_vec();
+    let mut ids_dfs: Vec<i32> = Vec::with_capacity(raw_navigation_children.len());
+
+    while let Some(id) = stack.pop() {
+        if let Some(raw_navigation_child) = raw_navigation_children.get(&id) {
+            ids_dfs.push(id);
+            stack.extend(raw_navigation_child.children.iter());
         }
-    };
+    }
+
+    ids_dfs
 }
 
 macro_rules! to_navigation {
-    ($instance: expr, $pages: expr, $raw_navigation_children: expr, $ids_dfs: expr) => {{
+    ($instance: expr, $root_ids: expr, $raw_navigation_children: expr) => {{
+        let mut ids_dfs = get_ids_dfs(&$root_ids, &$raw_navigation_children);
+
         let mut navigation_children: HashMap<i32, NavigationChild> =
             HashMap::with_capacity($raw_navigation_children.len());
 
-        while let Some(id) = $ids_dfs.pop() {
+        while let Some(id) = ids_dfs.pop() {
             if let Some(raw_navigation_child) = $raw_navigation_children.remove(&id) {
                 let children: Vec<NavigationChild> = raw_navigation_child
                     .children
@@ -93,9 +84,9 @@ macro_rules! to_navigation {
             }
         }
 
-        let data = $pages
+        let data = $root_ids
             .iter()
-            .filter_map(|page| navigation_children.remove(&page.id))
+            .filter_map(|id| navigation_children.remove(id))
             .collect();
 
         Ok(Navigation {
@@ -110,23 +101,10 @@ impl Navigation {
         instance: Instance,
         pool: &MySqlPool,
     ) -> Result<Navigation, NavigationError> {
-        let pages = fetch_all_pages!(instance, pool).await?;
-
-        let mut raw_navigation_children: HashMap<i32, RawNavigationChild> = HashMap::new();
-        let mut stack: Vec<i32> = pages.iter().map(|page| page.id).collect();
-        let mut ids_dfs: Vec<i32> = Vec::new();
-
-        while let Some(id) = stack.pop() {
-            handle_raw_navigation_child!(
-                id,
-                RawNavigationChild::fetch(id, pool).await,
-                raw_navigation_children,
-                stack,
-                ids_dfs
-            );
-        }
+        let (root_ids, mut raw_navigation_children) =
+            RawNavigationChild::fetch_all(&instance, pool).await?;
 
-        to_navigation!(instance, pages, raw_navigation_children, ids_dfs)
+        to_navigation!(instance, root_ids, raw_navigation_children)
     }
 
     #[allow(dead_code)]
@@ -137,28 +115,34 @@ impl Navigation {
     where
         E: Executor<'a>,
     {
-        let mut transaction = executor.begin().await?;
-
-        let pages = fetch_all_pages!(instance, &mut transaction).await?;
-
-        let mut raw_navigation_children: HashMap<i32, RawNavigationChild> = HashMap::new();
-        let mut stack: Vec<i32> = pages.iter().map(|page| page.id).collect();
-        let mut ids_dfs: Vec<i32> = Vec::new();
-
-        while let Some(id) = stack.pop() {
-            handle_raw_navigation_child!(
-                id,
-                RawNavigationChild::fetch_via_transaction(id, &mut transaction).await,
-                raw_navigation_children,
-                stack,
-                ids_dfs
-            );
-        }
+        let (root_ids, mut raw_navigation_children) =
+            RawNavigationChild::fetch_all_via_transaction(&instance, executor).await?;
 
-        let result = to_navigation!(instance, pages, raw_navigation_children, ids_dfs);
-
-        transaction.commit().await?;
+        to_navigation!(instance, root_ids, raw_navigation_children)
+    }
+}
 
-        result
+#[cfg(test)]
+mod tests {
+    use super::Navigation;
+    use crate::create_database_pool;
+    use crate::instance::Instance;
+
+    #[actix_rt::test]
+    async fn fetch_and_fetch_via_transaction_agree() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        let navigation = Navigation::fetch(Instance::De, &pool).await.unwrap();
+        let navigation_via_transaction =
+            Navigation::fetch_via_transaction(Instance::De, &mut transaction)
+                .await
+                .unwrap();
+
+        assert!(!navigation.data.is_empty());
+        assert_eq!(
+            serde_json::to_value(&navigation).unwrap(),
+            serde_json::to_value(&navigation_via_transaction).unwrap()
+        );
     }
 }
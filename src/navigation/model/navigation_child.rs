@@ -8,6 +8,7 @@ use sqlx::MySqlPool;
 use thiserror::Error;
 
 use crate::database::Executor;
+use crate::instance::Instance;
 
 #[derive(Serialize)]
 #[serde(untagged)]
@@ -54,6 +55,35 @@ pub enum NavigationChildError {
     Unsupported,
 }
 
+impl NavigationChild {
+    /// Returns the id of the UUID the child links to (if any).
+    pub fn uuid_id(&self) -> Option<i32> {
+        match self {
+            NavigationChild::Uuid(child) => Some(child.id),
+            _ => None,
+        }
+    }
+
+    pub fn children(&self) -> &[NavigationChild] {
+        let children = match self {
+            NavigationChild::Uuid(child) => &child.children,
+            NavigationChild::Url(child) => &child.children,
+            NavigationChild::Container(child) => &child.children,
+        };
+        children.as_deref().unwrap_or(&[])
+    }
+
+    /// Removes and returns the children, leaving the child without any.
+    pub fn take_children(&mut self) -> Vec<NavigationChild> {
+        let children = match self {
+            NavigationChild::Uuid(child) => &mut child.children,
+            NavigationChild::Url(child) => &mut child.children,
+            NavigationChild::Container(child) => &mut child.children,
+        };
+        children.take().unwrap_or_default()
+    }
+}
+
 impl From<sqlx::Error> for NavigationChildError {
     fn from(inner: sqlx::Error) -> Self {
         NavigationChildError::DatabaseError { inner }
@@ -182,11 +212,7 @@ impl TryFrom<(RawNavigationChild, Vec<NavigationChild>)> for NavigationChild {
                         Err(NavigationChildError::MissingRequiredRouteParameter)
                     }
                 }
-                route => {
-                    println!("Unhandled route: {}", route);
-                    println!("Params: {:?}", raw_navigation_child.parameters);
-                    Err(NavigationChildError::InvalidRoute)
-                }
+                _ => Err(NavigationChildError::InvalidRoute),
             }
         } else {
             Err(NavigationChildError::Unsupported)
@@ -200,11 +226,15 @@ pub struct RawNavigationChild {
     pub parameters: RawNavigationChildParameters,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct RawNavigationChildParameters(HashMap<String, String>);
 
 impl RawNavigationChildParameters {
-    fn get(&self, name: &str) -> Option<String> {
+    pub fn new(parameters: HashMap<String, String>) -> Self {
+        RawNavigationChildParameters(parameters)
+    }
+
+    pub fn get(&self, name: &str) -> Option<String> {
         self.0.get(name).map(|value| value.to_string())
     }
 
@@ -225,26 +255,29 @@ impl From<sqlx::Error> for RawNavigationChildError {
     }
 }
 
-macro_rules! fetch_all_children {
-    ($id: expr, $executor: expr) => {
+macro_rules! fetch_all_pages {
+    ($instance: expr, $executor: expr) => {
         sqlx::query!(
             r#"
-                SELECT p.id
+                SELECT p.id, p.parent_id
                     FROM navigation_page p
-                    WHERE p.parent_id = ?
+                    JOIN navigation_container c ON c.id = p.container_id
+                    JOIN instance i ON i.id = c.instance_id
+                    JOIN type t ON t.id = c.type_id
+                    WHERE i.subdomain = ? AND t.name = 'default'
                     ORDER BY p.position, p.id
             "#,
-            $id
+            $instance
         )
         .fetch_all($executor)
     };
 }
 
 macro_rules! fetch_all_parameters {
-    ($id: expr, $executor: expr) => {
+    ($instance: expr, $executor: expr) => {
         sqlx::query!(
             r#"
-                SELECT name, value FROM
+                SELECT u.page_id, u.name, u.value FROM
                     (
                     # Level 1
                     SELECT k.name, p.value, p.page_id
@@ -270,63 +303,95 @@ macro_rules! fetch_all_parameters {
                         JOIN navigation_parameter_key k3 ON k3.id = p3.key_id
                         WHERE p1.parent_id IS NULL AND p3.value != ''
                     ) u
-                    WHERE page_id = ?
+                    JOIN navigation_page p ON p.id = u.page_id
+                    JOIN navigation_container c ON c.id = p.container_id
+                    JOIN instance i ON i.id = c.instance_id
+                    JOIN type t ON t.id = c.type_id
+                    WHERE i.subdomain = ? AND t.name = 'default'
             "#,
-            $id
+            $instance
         )
         .fetch_all($executor)
     }
 }
 
-macro_rules! to_raw_navigation_child {
-    ($id: expr, $pages: expr, $params: expr) => {{
-        let children = $pages.iter().map(|page| page.id).collect();
-        let parameters = $params
-            .into_iter()
-            .filter_map(|param| Some((param.name?, param.value?)))
-            .collect();
-        let parameters = RawNavigationChildParameters(parameters);
+macro_rules! to_raw_navigation_children {
+    ($pages: expr, $params: expr) => {{
+        let mut parameters: HashMap<i32, HashMap<String, String>> = HashMap::new();
+        for param in $params.into_iter() {
+            if let (Some(page_id), Some(name), Some(value)) =
+                (param.page_id, param.name, param.value)
+            {
+                parameters
+                    .entry(page_id)
+                    .or_insert_with(HashMap::new)
+                    .insert(name, value);
+            }
+        }
 
-        RawNavigationChild {
-            id: $id,
-            children,
-            parameters,
+        // The pages are ordered by position, so the children are as well.
+        let mut children: HashMap<i32, Vec<i32>> = HashMap::new();
+        let mut root_ids: Vec<i32> = Vec::new();
+        for page in $pages.iter() {
+            match page.parent_id {
+                Some(parent_id) => children
+                    .entry(parent_id)
+                    .or_insert_with(Vec::new)
+                    .push(page.id),
+                None => root_ids.push(page.id),
+            }
         }
+
+        let raw_navigation_children = $pages
+            .iter()
+            .map(|page| {
+                (
+                    page.id,
+                    RawNavigationChild {
+                        id: page.id,
+                        children: children.remove(&page.id).unwrap_or_default(),
+                        parameters: RawNavigationChildParameters(
+                            parameters.remove(&page.id).unwrap_or_default(),
+                        ),
+                    },
+                )
+            })
+            .collect();
+
+        (root_ids, raw_navigation_children)
     }};
 }
 
 impl RawNavigationChild {
-    pub async fn fetch(
-        id: i32,
+    /// Fetches all pages of the default navigation of the instance with two queries. Returns the
+    /// ids of the top-level pages and the pages by their id.
+    pub async fn fetch_all(
+        instance: &Instance,
         pool: &MySqlPool,
-    ) -> Result<RawNavigationChild, RawNavigationChildError> {
-        let pages = fetch_all_children!(id, pool);
-        let params = fetch_all_parameters!(id, pool);
+    ) -> Result<(Vec<i32>, HashMap<i32, RawNavigationChild>), RawNavigationChildError> {
+        let pages = fetch_all_pages!(instance, pool);
+        let params = fetch_all_parameters!(instance, pool);
 
         let (pages, params) = try_join!(pages, params)?;
 
-        let raw_navigation_child = to_raw_navigation_child!(id, pages, params);
-        Ok(raw_navigation_child)
+        Ok(to_raw_navigation_children!(pages, params))
     }
 
-    pub async fn fetch_via_transaction<'e, 'c, E>(
-        id: i32,
+    pub async fn fetch_all_via_transaction<'a, E>(
+        instance: &Instance,
         executor: E,
-    ) -> Result<RawNavigationChild, RawNavigationChildError>
+    ) -> Result<(Vec<i32>, HashMap<i32, RawNavigationChild>), RawNavigationChildError>
     where
-        'c: 'e,
-        E: 'c + Executor<'e>,
+        E: Executor<'a>,
     {
         let mut transaction = executor.begin().await?;
 
-        let pages = fetch_all_children!(id, &mut transaction).await?;
-        let params = fetch_all_parameters!(id, &mut transaction).await?;
-
-        let raw_navigation_child = to_raw_navigation_child!(id, pages, params);
+        let pages = fetch_all_pages!(instance, &mut transaction).await?;
+        let params = fetch_all_parameters!(instance, &mut transaction).await?;
 
         transaction.commit().await?;
 
-        Ok(raw_navigation_child)
+        Ok(to_raw_navigation_children!(pages, params))
     }
 
     pub fn is_visible(&self) -> bool {
@@ -0,0 +1,357 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+use serde::Serialize;
+use sqlx::MySqlPool;
+
+use super::navigation::NavigationError;
+use super::navigation_child::{
+    NavigationChild, NavigationChildError, RawNavigationChild, RawNavigationChildParameters,
+};
+use crate::database::{placeholders, Executor};
+use crate::instance::Instance;
+
+/// Lists the pages of the navigation that are not part of `Navigation` and the UUID entries that
+/// point to trashed or nonexistent UUIDs.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavigationDiagnostics {
+    pub instance: Instance,
+    pub dropped_nodes: Vec<DroppedNavigationNode>,
+    pub invalid_uuid_nodes: Vec<InvalidUuidNavigationNode>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DroppedNavigationNode {
+    pub id: i32,
+    pub reason: NavigationNodeDropReason,
+    pub parameters: RawNavigationChildParameters,
+    pub path: Vec<NavigationPathEntry>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NavigationNodeDropReason {
+    NotVisible,
+    InvalidRoute,
+    MissingRequiredRouteParameter,
+    Unsupported,
+    /// The node itself is valid but one of its ancestors is dropped.
+    AncestorDropped,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvalidUuidNavigationNode {
+    pub id: i32,
+    pub uuid: i32,
+    pub reason: InvalidUuidReason,
+    pub path: Vec<NavigationPathEntry>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InvalidUuidReason {
+    Trashed,
+    NotFound,
+}
+
+/// One page on the way from the top level of the navigation to a node (including the node itself).
+#[derive(Clone, Serialize)]
+pub struct NavigationPathEntry {
+    pub id: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl NavigationDiagnostics {
+    pub async fn fetch(
+        instance: Instance,
+        pool: &MySqlPool,
+    ) -> Result<NavigationDiagnostics, NavigationError> {
+        Self::fetch_via_transaction(instance, pool).await
+    }
+
+    pub async fn fetch_via_transaction<'a, E>(
+        instance: Instance,
+        executor: E,
+    ) -> Result<NavigationDiagnostics, NavigationError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        let (root_ids, mut raw_navigation_children) =
+            RawNavigationChild::fetch_all_via_transaction(&instance, &mut transaction).await?;
+
+        let mut dropped_nodes = Vec::new();
+        let mut uuid_nodes: Vec<(i32, i32, Vec<NavigationPathEntry>)> = Vec::new();
+
+        // Walk the tree in document order, remembering the path and whether an ancestor is dropped.
+        let mut stack: Vec<(i32, Vec<NavigationPathEntry>, bool)> = root_ids
+            .iter()
+            .rev()
+            .map(|id| (*id, Vec::new(), false))
+            .collect();
+
+        while let Some((id, mut path, ancestor_dropped)) = stack.pop() {
+            let raw_navigation_child = match raw_navigation_children.remove(&id) {
+                Some(raw_navigation_child) => raw_navigation_child,
+                None => continue,
+            };
+            path.push(NavigationPathEntry {
+                id,
+                label: raw_navigation_child.parameters.get("label"),
+            });
+            let children = raw_navigation_child.children.clone();
+            let parameters = raw_navigation_child.parameters.clone();
+
+            // Hidden pages are still part of the navigation, but the frontend doesn't show them.
+            let reason = if !raw_navigation_child.is_visible() {
+                Some(NavigationNodeDropReason::NotVisible)
+            } else {
+                match NavigationChild::try_from((raw_navigation_child, Vec::new())) {
+                    Ok(navigation_child) => {
+                        if let NavigationChild::Uuid(uuid_navigation_child) = navigation_child {
+                            uuid_nodes.push((id, uuid_navigation_child.id, path.clone()));
+                        }
+                        if ancestor_dropped {
+                            Some(NavigationNodeDropReason::AncestorDropped)
+                        } else {
+                            None
+                        }
+                    }
+                    Err(error) => Some(match error {
+                        NavigationChildError::DatabaseError { inner } => return Err(inner.into()),
+                        NavigationChildError::NotVisible => NavigationNodeDropReason::NotVisible,
+                        NavigationChildError::InvalidRoute => {
+                            NavigationNodeDropReason::InvalidRoute
+                        }
+                        NavigationChildError::MissingRequiredRouteParameter => {
+                            NavigationNodeDropReason::MissingRequiredRouteParameter
+                        }
+                        NavigationChildError::Unsupported => NavigationNodeDropReason::Unsupported,
+                    }),
+                }
+            };
+
+            let dropped = reason.is_some();
+            if let Some(reason) = reason {
+                dropped_nodes.push(DroppedNavigationNode {
+                    id,
+                    reason,
+                    parameters,
+                    path: path.clone(),
+                });
+            }
+
+            for child_id in children.iter().rev() {
+                stack.push((*child_id, path.clone(), dropped));
+            }
+        }
+
+        let invalid_uuid_nodes = if uuid_nodes.is_empty() {
+            Vec::new()
+        } else {
+            let query = format!(
+                "SELECT id, trashed FROM uuid WHERE id IN ({})",
+                placeholders(uuid_nodes.len())
+            );
+            let mut query = sqlx::query_as::<_, (i64, i8)>(&query);
+            for (_, uuid, _) in uuid_nodes.iter() {
+                query = query.bind(uuid);
+            }
+            let uuids = query.fetch_all(&mut transaction).await?;
+            let trashed: HashMap<i32, bool> = uuids
+                .into_iter()
+                .map(|(id, trashed)| (id as i32, trashed != 0))
+                .collect();
+
+            uuid_nodes
+                .into_iter()
+                .filter_map(|(id, uuid, path)| {
+                    let reason = match trashed.get(&uuid) {
+                        Some(false) => return None,
+                        Some(true) => InvalidUuidReason::Trashed,
+                        None => InvalidUuidReason::NotFound,
+                    };
+                    Some(InvalidUuidNavigationNode {
+                        id,
+                        uuid,
+                        reason,
+                        path,
+                    })
+                })
+                .collect()
+        };
+
+        transaction.commit().await?;
+
+        Ok(NavigationDiagnostics {
+            instance,
+            dropped_nodes,
+            invalid_uuid_nodes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InvalidUuidReason, NavigationDiagnostics, NavigationNodeDropReason};
+    use crate::create_database_pool;
+    use crate::instance::Instance;
+    use crate::navigation::{
+        Navigation, NavigationPage, NavigationPageCreatePayload, NavigationPageParameters,
+    };
+
+    fn parameters(label: &str, route: Option<&str>, uri: Option<&str>) -> NavigationPageParameters {
+        NavigationPageParameters {
+            label: label.to_string(),
+            uri: uri.map(|uri| uri.to_string()),
+            route: route.map(|route| route.to_string()),
+            params: Default::default(),
+            visible: None,
+        }
+    }
+
+    #[actix_rt::test]
+    async fn reports_hidden_nodes_as_not_visible() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        let hidden = NavigationPage::create(
+            NavigationPageCreatePayload {
+                instance: Instance::De,
+                parent_id: None,
+                position: None,
+                parameters: NavigationPageParameters {
+                    visible: Some(false),
+                    ..parameters("Hidden", None, Some("#"))
+                },
+            },
+            &mut transaction,
+        )
+        .await
+        .unwrap();
+        let child = NavigationPage::create(
+            NavigationPageCreatePayload {
+                instance: Instance::De,
+                parent_id: Some(hidden.id),
+                position: None,
+                parameters: parameters("Child", None, Some("/blog")),
+            },
+            &mut transaction,
+        )
+        .await
+        .unwrap();
+
+        let diagnostics =
+            NavigationDiagnostics::fetch_via_transaction(Instance::De, &mut transaction)
+                .await
+                .unwrap();
+
+        let reason = |id| {
+            diagnostics
+                .dropped_nodes
+                .iter()
+                .find(|node| node.id == id)
+                .map(|node| &node.reason)
+        };
+        assert_eq!(
+            reason(hidden.id),
+            Some(&NavigationNodeDropReason::NotVisible)
+        );
+        assert_eq!(
+            reason(child.id),
+            Some(&NavigationNodeDropReason::AncestorDropped)
+        );
+
+        // The navigation itself still contains the hidden page.
+        let navigation = Navigation::fetch_via_transaction(Instance::De, &mut transaction)
+            .await
+            .unwrap();
+        let data = serde_json::to_string(&navigation.data).unwrap();
+        assert!(data.contains(r#""label":"Hidden""#));
+    }
+
+    #[actix_rt::test]
+    async fn reports_dropped_nodes_and_invalid_uuids() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        let container = NavigationPage::create(
+            NavigationPageCreatePayload {
+                instance: Instance::De,
+                parent_id: None,
+                position: None,
+                parameters: parameters("Container", None, Some("#")),
+            },
+            &mut transaction,
+        )
+        .await
+        .unwrap();
+        let missing_uuid = NavigationPage::create(
+            NavigationPageCreatePayload {
+                instance: Instance::De,
+                parent_id: Some(container.id),
+                position: None,
+                parameters: parameters("Missing", None, Some("/999999999")),
+            },
+            &mut transaction,
+        )
+        .await
+        .unwrap();
+        let broken = NavigationPage::create(
+            NavigationPageCreatePayload {
+                instance: Instance::De,
+                parent_id: Some(container.id),
+                position: None,
+                parameters: parameters("Broken", Some("blog"), None),
+            },
+            &mut transaction,
+        )
+        .await
+        .unwrap();
+        // Invalid routes are rejected by the mutation, so we need to write them directly.
+        sqlx::query!(
+            "UPDATE navigation_parameter SET value = 'unknown/route' WHERE page_id = ? AND value = 'blog'",
+            broken.id
+        )
+        .execute(&mut transaction)
+        .await
+        .unwrap();
+
+        let diagnostics =
+            NavigationDiagnostics::fetch_via_transaction(Instance::De, &mut transaction)
+                .await
+                .unwrap();
+
+        let dropped = diagnostics
+            .dropped_nodes
+            .iter()
+            .find(|node| node.id == broken.id)
+            .unwrap();
+        assert_eq!(dropped.reason, NavigationNodeDropReason::InvalidRoute);
+        assert_eq!(
+            dropped
+                .path
+                .iter()
+                .map(|entry| entry.id)
+                .collect::<Vec<_>>(),
+            vec![container.id, broken.id]
+        );
+        assert_eq!(
+            dropped.parameters.get("route"),
+            Some("unknown/route".to_string())
+        );
+
+        let invalid_uuid = diagnostics
+            .invalid_uuid_nodes
+            .iter()
+            .find(|node| node.id == missing_uuid.id)
+            .unwrap();
+        assert_eq!(invalid_uuid.uuid, 999999999);
+        assert_eq!(invalid_uuid.reason, InvalidUuidReason::NotFound);
+    }
+}
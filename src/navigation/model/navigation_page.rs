@@ -0,0 +1,797 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::navigation_child::{
+    NavigationChild, NavigationChildError, RawNavigationChild, RawNavigationChildParameters,
+};
+use crate::database::Executor;
+use crate::instance::Instance;
+
+/// A page of the navigation as stored in `navigation_page` / `navigation_parameter`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavigationPage {
+    pub id: i32,
+    pub parent_id: Option<i32>,
+    pub position: i32,
+    pub parameters: HashMap<String, String>,
+}
+
+/// The parameters of a navigation page. Exactly one of `uri` and `route` has to be set. `params`
+/// are the parameters of the route (stored as `params.*`).
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavigationPageParameters {
+    pub label: String,
+    pub uri: Option<String>,
+    pub route: Option<String>,
+    #[serde(default)]
+    pub params: HashMap<String, String>,
+    pub visible: Option<bool>,
+}
+
+impl NavigationPageParameters {
+    fn to_flat_parameters(&self) -> HashMap<String, String> {
+        let mut parameters = HashMap::new();
+        parameters.insert("label".to_string(), self.label.clone());
+        if let Some(uri) = &self.uri {
+            parameters.insert("uri".to_string(), uri.clone());
+        }
+        if let Some(route) = &self.route {
+            parameters.insert("route".to_string(), route.clone());
+        }
+        for (name, value) in self.params.iter() {
+            parameters.insert(format!("params.{}", name), value.clone());
+        }
+        if self.visible == Some(false) {
+            parameters.insert("visible".to_string(), "false".to_string());
+        }
+        parameters
+    }
+
+    /// Makes sure that the navigation page is understood by [`NavigationChild::try_from`], so that
+    /// it is not silently dropped from the navigation.
+    fn validate(&self) -> Result<(), NavigationPageError> {
+        if self.label.is_empty() || self.uri.is_some() == self.route.is_some() {
+            return Err(NavigationPageError::InvalidParameters {
+                inner: NavigationChildError::Unsupported,
+            });
+        }
+        let raw_navigation_child = RawNavigationChild {
+            id: 0,
+            children: vec![],
+            parameters: RawNavigationChildParameters::new(self.to_flat_parameters()),
+        };
+        NavigationChild::try_from((raw_navigation_child, vec![]))
+            .map(|_| ())
+            .map_err(|inner| NavigationPageError::InvalidParameters { inner })
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum NavigationPageError {
+    #[error("Navigation page cannot be changed because of a database error: {inner:?}.")]
+    DatabaseError { inner: sqlx::Error },
+    #[error("Navigation page cannot be changed because its parameters are invalid: {inner:?}.")]
+    InvalidParameters { inner: NavigationChildError },
+    #[error("Navigation page cannot be moved below itself or into another instance.")]
+    InvalidParent,
+    #[error("Navigation page cannot be changed because the instance has no navigation.")]
+    NavigationNotFound,
+    #[error("Navigation page cannot be changed because it does not exist.")]
+    NotFound,
+}
+
+impl From<sqlx::Error> for NavigationPageError {
+    fn from(inner: sqlx::Error) -> Self {
+        NavigationPageError::DatabaseError { inner }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavigationPageCreatePayload {
+    pub instance: Instance,
+    pub parent_id: Option<i32>,
+    /// Position among the siblings (starting at 0). The page is appended if not given.
+    pub position: Option<i32>,
+    #[serde(flatten)]
+    pub parameters: NavigationPageParameters,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavigationPageUpdatePayload {
+    pub id: i32,
+    #[serde(flatten)]
+    pub parameters: NavigationPageParameters,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavigationPageMovePayload {
+    pub id: i32,
+    pub parent_id: Option<i32>,
+    pub position: Option<i32>,
+}
+
+impl NavigationPage {
+    pub async fn fetch_via_transaction<'a, E>(
+        id: i32,
+        executor: E,
+    ) -> Result<NavigationPage, NavigationPageError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        let page = sqlx::query!(
+            r#"SELECT parent_id, position FROM navigation_page WHERE id = ?"#,
+            id
+        )
+        .fetch_optional(&mut transaction)
+        .await?
+        .ok_or(NavigationPageError::NotFound)?;
+        let parameters = fetch_parameters(id, &mut transaction).await?;
+
+        transaction.commit().await?;
+
+        Ok(NavigationPage {
+            id,
+            parent_id: page.parent_id,
+            position: page.position.unwrap_or(0),
+            parameters: parameters
+                .into_iter()
+                .map(|parameter| (parameter.name, parameter.value))
+                .collect(),
+        })
+    }
+
+    pub async fn create<'a, E>(
+        payload: NavigationPageCreatePayload,
+        executor: E,
+    ) -> Result<NavigationPage, NavigationPageError>
+    where
+        E: Executor<'a>,
+    {
+        payload.parameters.validate()?;
+
+        let mut transaction = executor.begin().await?;
+
+        let container_id = fetch_default_container_id(&payload.instance, &mut transaction).await?;
+        if let Some(parent_id) = payload.parent_id {
+            if fetch_container_id(parent_id, &mut transaction).await? != container_id {
+                return Err(NavigationPageError::InvalidParent);
+            }
+        }
+
+        sqlx::query!(
+            r#"
+                INSERT INTO navigation_page (container_id, parent_id, position)
+                    VALUES (?, ?, ?)
+            "#,
+            container_id,
+            payload.parent_id,
+            i32::MAX
+        )
+        .execute(&mut transaction)
+        .await?;
+        let id = sqlx::query!(r#"SELECT LAST_INSERT_ID() as id"#)
+            .fetch_one(&mut transaction)
+            .await?
+            .id as i32;
+
+        save_parameters(
+            id,
+            &payload.parameters.to_flat_parameters(),
+            &mut transaction,
+        )
+        .await?;
+        insert_into_siblings(
+            id,
+            container_id,
+            payload.parent_id,
+            payload.position,
+            &mut transaction,
+        )
+        .await?;
+
+        let page = Self::fetch_via_transaction(id, &mut transaction).await?;
+
+        transaction.commit().await?;
+
+        Ok(page)
+    }
+
+    /// Replaces all parameters of the navigation page.
+    pub async fn update<'a, E>(
+        payload: NavigationPageUpdatePayload,
+        executor: E,
+    ) -> Result<NavigationPage, NavigationPageError>
+    where
+        E: Executor<'a>,
+    {
+        payload.parameters.validate()?;
+
+        let mut transaction = executor.begin().await?;
+
+        fetch_container_id(payload.id, &mut transaction).await?;
+        delete_parameters(payload.id, &mut transaction).await?;
+        save_parameters(
+            payload.id,
+            &payload.parameters.to_flat_parameters(),
+            &mut transaction,
+        )
+        .await?;
+
+        let page = Self::fetch_via_transaction(payload.id, &mut transaction).await?;
+
+        transaction.commit().await?;
+
+        Ok(page)
+    }
+
+    /// Moves the navigation page (with its children) below another parent (or to the top level if
+    /// `parent_id` is `None`) within the same navigation.
+    pub async fn move_page<'a, E>(
+        payload: NavigationPageMovePayload,
+        executor: E,
+    ) -> Result<NavigationPage, NavigationPageError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        let page = Self::fetch_via_transaction(payload.id, &mut transaction).await?;
+        let container_id = fetch_container_id(payload.id, &mut transaction).await?;
+
+        if let Some(parent_id) = payload.parent_id {
+            if fetch_container_id(parent_id, &mut transaction).await? != container_id {
+                return Err(NavigationPageError::InvalidParent);
+            }
+            let descendants = fetch_descendants(payload.id, &mut transaction).await?;
+            if parent_id == payload.id || descendants.contains(&parent_id) {
+                return Err(NavigationPageError::InvalidParent);
+            }
+        }
+
+        sqlx::query!(
+            r#"UPDATE navigation_page SET parent_id = ? WHERE id = ?"#,
+            payload.parent_id,
+            payload.id
+        )
+        .execute(&mut transaction)
+        .await?;
+        normalize_positions(container_id, page.parent_id, &mut transaction).await?;
+        insert_into_siblings(
+            payload.id,
+            container_id,
+            payload.parent_id,
+            payload.position,
+            &mut transaction,
+        )
+        .await?;
+
+        let page = Self::fetch_via_transaction(payload.id, &mut transaction).await?;
+
+        transaction.commit().await?;
+
+        Ok(page)
+    }
+
+    /// Deletes the navigation page together with all its children.
+    pub async fn delete<'a, E>(id: i32, executor: E) -> Result<(), NavigationPageError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        let page = Self::fetch_via_transaction(id, &mut transaction).await?;
+        let container_id = fetch_container_id(id, &mut transaction).await?;
+
+        let mut ids = vec![id];
+        ids.extend(fetch_descendants(id, &mut transaction).await?);
+
+        // Children are deleted before their parents.
+        for id in ids.into_iter().rev() {
+            delete_parameters(id, &mut transaction).await?;
+            sqlx::query!(r#"DELETE FROM navigation_page WHERE id = ?"#, id)
+                .execute(&mut transaction)
+                .await?;
+        }
+
+        normalize_positions(container_id, page.parent_id, &mut transaction).await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+}
+
+struct NavigationParameter {
+    name: String,
+    value: String,
+}
+
+async fn fetch_parameters<'a, E>(
+    page_id: i32,
+    executor: E,
+) -> Result<Vec<NavigationParameter>, sqlx::Error>
+where
+    E: Executor<'a>,
+{
+    let parameters = sqlx::query!(
+        r#"
+            SELECT p.id, p.parent_id, k.name, p.value
+                FROM navigation_parameter p
+                JOIN navigation_parameter_key k ON k.id = p.key_id
+                WHERE p.page_id = ?
+        "#,
+        page_id
+    )
+    .fetch_all(executor)
+    .await?;
+
+    let parameters_by_id: HashMap<i32, (Option<i32>, String)> = parameters
+        .iter()
+        .map(|parameter| (parameter.id, (parameter.parent_id, parameter.name.clone())))
+        .collect();
+
+    Ok(parameters
+        .into_iter()
+        .filter_map(|parameter| {
+            let value = parameter.value;
+            if value.is_empty() {
+                return None;
+            }
+            let mut name = parameter.name;
+            let mut parent_id = parameter.parent_id;
+            while let Some((grand_parent_id, parent_name)) =
+                parent_id.and_then(|id| parameters_by_id.get(&id))
+            {
+                name = format!("{}.{}", parent_name, name);
+                parent_id = *grand_parent_id;
+            }
+            Some(NavigationParameter { name, value })
+        })
+        .collect())
+}
+
+/// Saves the parameters (like `params.id`) as a tree of `navigation_parameter` rows, like the
+/// legacy serlo.org application does.
+async fn save_parameters<'a, E>(
+    page_id: i32,
+    parameters: &HashMap<String, String>,
+    executor: E,
+) -> Result<(), sqlx::Error>
+where
+    E: Executor<'a>,
+{
+    let mut transaction = executor.begin().await?;
+
+    let mut parameter_ids: HashMap<String, i32> = HashMap::new();
+
+    let mut names: Vec<&String> = parameters.keys().collect();
+    names.sort();
+
+    for name in names {
+        let mut parent_id: Option<i32> = None;
+        let mut path = String::new();
+        let segments: Vec<&str> = name.split('.').collect();
+
+        for (index, segment) in segments.iter().enumerate() {
+            if !path.is_empty() {
+                path.push('.');
+            }
+            path.push_str(segment);
+
+            let is_leaf = index == segments.len() - 1;
+            if !is_leaf {
+                if let Some(id) = parameter_ids.get(&path) {
+                    parent_id = Some(*id);
+                    continue;
+                }
+            }
+
+            let key_id = fetch_or_create_key_id(segment, &mut transaction).await?;
+            let value = if is_leaf {
+                parameters[name].as_str()
+            } else {
+                ""
+            };
+            sqlx::query!(
+                r#"
+                    INSERT INTO navigation_parameter (page_id, parent_id, key_id, value)
+                        VALUES (?, ?, ?, ?)
+                "#,
+                page_id,
+                parent_id,
+                key_id,
+                value
+            )
+            .execute(&mut transaction)
+            .await?;
+            let id = sqlx::query!(r#"SELECT LAST_INSERT_ID() as id"#)
+                .fetch_one(&mut transaction)
+                .await?
+                .id as i32;
+
+            parameter_ids.insert(path.clone(), id);
+            parent_id = Some(id);
+        }
+    }
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+async fn delete_parameters<'a, E>(page_id: i32, executor: E) -> Result<(), sqlx::Error>
+where
+    E: Executor<'a>,
+{
+    let mut transaction = executor.begin().await?;
+
+    let parameters = sqlx::query!(
+        r#"SELECT id, parent_id FROM navigation_parameter WHERE page_id = ?"#,
+        page_id
+    )
+    .fetch_all(&mut transaction)
+    .await?;
+
+    // Nested parameters are deleted before their parents.
+    let mut remaining: Vec<(i32, Option<i32>)> = parameters
+        .iter()
+        .map(|parameter| (parameter.id, parameter.parent_id))
+        .collect();
+    while !remaining.is_empty() {
+        let (leaves, rest): (Vec<_>, Vec<_>) = remaining.iter().partition(|(id, _)| {
+            !remaining
+                .iter()
+                .any(|(_, parent_id)| *parent_id == Some(*id))
+        });
+        for (id, _) in leaves {
+            sqlx::query!(r#"DELETE FROM navigation_parameter WHERE id = ?"#, id)
+                .execute(&mut transaction)
+                .await?;
+        }
+        remaining = rest;
+    }
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+async fn fetch_or_create_key_id<'a, E>(name: &str, executor: E) -> Result<i32, sqlx::Error>
+where
+    E: Executor<'a>,
+{
+    let mut transaction = executor.begin().await?;
+
+    let key = sqlx::query!(
+        r#"SELECT id FROM navigation_parameter_key WHERE name = ?"#,
+        name
+    )
+    .fetch_optional(&mut transaction)
+    .await?;
+
+    let id = match key {
+        Some(key) => key.id,
+        None => {
+            sqlx::query!(
+                r#"INSERT INTO navigation_parameter_key (name) VALUES (?)"#,
+                name
+            )
+            .execute(&mut transaction)
+            .await?;
+            sqlx::query!(r#"SELECT LAST_INSERT_ID() as id"#)
+                .fetch_one(&mut transaction)
+                .await?
+                .id as i32
+        }
+    };
+
+    transaction.commit().await?;
+
+    Ok(id)
+}
+
+async fn fetch_container_id<'a, E>(page_id: i32, executor: E) -> Result<i32, NavigationPageError>
+where
+    E: Executor<'a>,
+{
+    sqlx::query!(
+        r#"SELECT container_id FROM navigation_page WHERE id = ?"#,
+        page_id
+    )
+    .fetch_optional(executor)
+    .await?
+    .map(|page| page.container_id)
+    .ok_or(NavigationPageError::NotFound)
+}
+
+async fn fetch_default_container_id<'a, E>(
+    instance: &Instance,
+    executor: E,
+) -> Result<i32, NavigationPageError>
+where
+    E: Executor<'a>,
+{
+    sqlx::query!(
+        r#"
+            SELECT c.id
+                FROM navigation_container c
+                JOIN instance i ON i.id = c.instance_id
+                JOIN type t ON t.id = c.type_id
+                WHERE i.subdomain = ? AND t.name = 'default'
+        "#,
+        instance
+    )
+    .fetch_optional(executor)
+    .await?
+    .map(|container| container.id)
+    .ok_or(NavigationPageError::NavigationNotFound)
+}
+
+async fn fetch_descendants<'a, E>(id: i32, executor: E) -> Result<Vec<i32>, sqlx::Error>
+where
+    E: Executor<'a>,
+{
+    let mut transaction = executor.begin().await?;
+
+    let mut descendants = Vec::new();
+    let mut stack = vec![id];
+    while let Some(id) = stack.pop() {
+        let children = sqlx::query!(r#"SELECT id FROM navigation_page WHERE parent_id = ?"#, id)
+            .fetch_all(&mut transaction)
+            .await?;
+        for child in children {
+            descendants.push(child.id);
+            stack.push(child.id);
+        }
+    }
+
+    transaction.commit().await?;
+
+    Ok(descendants)
+}
+
+async fn fetch_sibling_ids<'a, E>(
+    container_id: i32,
+    parent_id: Option<i32>,
+    executor: E,
+) -> Result<Vec<i32>, sqlx::Error>
+where
+    E: Executor<'a>,
+{
+    let siblings = sqlx::query!(
+        r#"
+            SELECT id
+                FROM navigation_page
+                WHERE container_id = ? AND parent_id <=> ?
+                ORDER BY position, id
+        "#,
+        container_id,
+        parent_id
+    )
+    .fetch_all(executor)
+    .await?;
+    Ok(siblings.iter().map(|sibling| sibling.id).collect())
+}
+
+async fn save_positions<'a, E>(ids: &[i32], executor: E) -> Result<(), sqlx::Error>
+where
+    E: Executor<'a>,
+{
+    let mut transaction = executor.begin().await?;
+
+    for (position, id) in ids.iter().enumerate() {
+        sqlx::query!(
+            r#"UPDATE navigation_page SET position = ? WHERE id = ?"#,
+            position as i32,
+            id
+        )
+        .execute(&mut transaction)
+        .await?;
+    }
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+/// Renumbers the positions of the siblings consecutively, starting at 0.
+async fn normalize_positions<'a, E>(
+    container_id: i32,
+    parent_id: Option<i32>,
+    executor: E,
+) -> Result<(), sqlx::Error>
+where
+    E: Executor<'a>,
+{
+    let mut transaction = executor.begin().await?;
+    let ids = fetch_sibling_ids(container_id, parent_id, &mut transaction).await?;
+    save_positions(&ids, &mut transaction).await?;
+    transaction.commit().await?;
+    Ok(())
+}
+
+/// Puts the navigation page at the given position among its siblings (or at the end) and
+/// renumbers the positions of the siblings.
+async fn insert_into_siblings<'a, E>(
+    id: i32,
+    container_id: i32,
+    parent_id: Option<i32>,
+    position: Option<i32>,
+    executor: E,
+) -> Result<(), sqlx::Error>
+where
+    E: Executor<'a>,
+{
+    let mut transaction = executor.begin().await?;
+
+    let mut ids: Vec<i32> = fetch_sibling_ids(container_id, parent_id, &mut transaction)
+        .await?
+        .into_iter()
+        .filter(|sibling_id| *sibling_id != id)
+        .collect();
+    let index = position
+        .map(|position| std::cmp::min(std::cmp::max(position, 0) as usize, ids.len()))
+        .unwrap_or_else(|| ids.len());
+    ids.insert(index, id);
+    save_positions(&ids, &mut transaction).await?;
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{
+        NavigationPage, NavigationPageCreatePayload, NavigationPageError,
+        NavigationPageMovePayload, NavigationPageParameters, NavigationPageUpdatePayload,
+    };
+    use crate::create_database_pool;
+    use crate::instance::Instance;
+    use crate::navigation::Navigation;
+
+    fn parameters(label: &str, route: &str, params: Vec<(&str, &str)>) -> NavigationPageParameters {
+        NavigationPageParameters {
+            label: label.to_string(),
+            uri: None,
+            route: Some(route.to_string()),
+            params: params
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect::<HashMap<_, _>>(),
+            visible: None,
+        }
+    }
+
+    #[actix_rt::test]
+    async fn create_navigation_page() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        let page = NavigationPage::create(
+            NavigationPageCreatePayload {
+                instance: Instance::De,
+                parent_id: None,
+                position: Some(0),
+                parameters: parameters("Neue Seite", "page/view", vec![("page", "19767")]),
+            },
+            &mut transaction,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(page.position, 0);
+        assert_eq!(page.parameters.get("params.page").unwrap(), "19767");
+
+        let navigation = Navigation::fetch_via_transaction(Instance::De, &mut transaction)
+            .await
+            .unwrap();
+        let navigation = serde_json::to_value(navigation).unwrap();
+        assert_eq!(navigation["data"][0]["label"], "Neue Seite");
+        assert_eq!(navigation["data"][0]["id"], 19767);
+    }
+
+    #[actix_rt::test]
+    async fn create_navigation_page_with_invalid_route() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        let result = NavigationPage::create(
+            NavigationPageCreatePayload {
+                instance: Instance::De,
+                parent_id: None,
+                position: None,
+                parameters: parameters("Kaputt", "page/view", vec![]),
+            },
+            &mut transaction,
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(NavigationPageError::InvalidParameters { .. })
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn update_move_and_delete_navigation_page() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        let parent = NavigationPage::create(
+            NavigationPageCreatePayload {
+                instance: Instance::De,
+                parent_id: None,
+                position: None,
+                parameters: parameters("Eltern", "blog", vec![]),
+            },
+            &mut transaction,
+        )
+        .await
+        .unwrap();
+        let child = NavigationPage::create(
+            NavigationPageCreatePayload {
+                instance: Instance::De,
+                parent_id: None,
+                position: None,
+                parameters: parameters("Kind", "taxonomy/term/get", vec![("term", "5")]),
+            },
+            &mut transaction,
+        )
+        .await
+        .unwrap();
+
+        let child = NavigationPage::update(
+            NavigationPageUpdatePayload {
+                id: child.id,
+                parameters: parameters("Kind 2", "taxonomy/term/get", vec![("term", "5")]),
+            },
+            &mut transaction,
+        )
+        .await
+        .unwrap();
+        assert_eq!(child.parameters.get("label").unwrap(), "Kind 2");
+
+        let child = NavigationPage::move_page(
+            NavigationPageMovePayload {
+                id: child.id,
+                parent_id: Some(parent.id),
+                position: None,
+            },
+            &mut transaction,
+        )
+        .await
+        .unwrap();
+        assert_eq!(child.parent_id, Some(parent.id));
+        assert_eq!(child.position, 0);
+
+        let result = NavigationPage::move_page(
+            NavigationPageMovePayload {
+                id: parent.id,
+                parent_id: Some(child.id),
+                position: None,
+            },
+            &mut transaction,
+        )
+        .await;
+        assert!(matches!(result, Err(NavigationPageError::InvalidParent)));
+
+        NavigationPage::delete(parent.id, &mut transaction)
+            .await
+            .unwrap();
+        let result = NavigationPage::fetch_via_transaction(child.id, &mut transaction).await;
+        assert!(matches!(result, Err(NavigationPageError::NotFound)));
+    }
+}
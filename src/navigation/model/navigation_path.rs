@@ -0,0 +1,204 @@
+use serde::Serialize;
+use sqlx::MySqlPool;
+use thiserror::Error;
+
+use super::navigation::{Navigation, NavigationError};
+use super::navigation_child::NavigationChild;
+use crate::database::Executor;
+use crate::instance::Instance;
+use crate::uuid::{Entity, TaxonomyTerm};
+
+/// Position of a UUID in the navigation: the ancestors leading to it (without their children) and
+/// the subtree of its siblings (including the UUID itself).
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavigationPath {
+    pub instance: Instance,
+    pub id: i32,
+    /// The taxonomy term that was found in the navigation instead of `id` (if any).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub taxonomy_term_id: Option<i32>,
+    pub path: Vec<NavigationChild>,
+    pub subtree: Vec<NavigationChild>,
+}
+
+#[derive(Error, Debug)]
+pub enum NavigationPathError {
+    #[error("NavigationPath cannot be fetched because of a database error: {inner:?}.")]
+    DatabaseError { inner: sqlx::Error },
+    #[error("NavigationPath cannot be fetched because the UUID is not part of the navigation.")]
+    NotFound,
+}
+
+impl From<sqlx::Error> for NavigationPathError {
+    fn from(inner: sqlx::Error) -> Self {
+        NavigationPathError::DatabaseError { inner }
+    }
+}
+
+impl From<NavigationError> for NavigationPathError {
+    fn from(error: NavigationError) -> Self {
+        match error {
+            NavigationError::DatabaseError { inner } => inner.into(),
+        }
+    }
+}
+
+impl NavigationPath {
+    pub async fn fetch(
+        instance: Instance,
+        id: i32,
+        include_taxonomy_fallback: bool,
+        pool: &MySqlPool,
+    ) -> Result<NavigationPath, NavigationPathError> {
+        Self::fetch_via_transaction(instance, id, include_taxonomy_fallback, pool).await
+    }
+
+    pub async fn fetch_via_transaction<'a, E>(
+        instance: Instance,
+        id: i32,
+        include_taxonomy_fallback: bool,
+        executor: E,
+    ) -> Result<NavigationPath, NavigationPathError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        let navigation =
+            Navigation::fetch_via_transaction(instance.clone(), &mut transaction).await?;
+
+        let (indices, taxonomy_term_id) = match find_indices(&navigation.data, id) {
+            Some(indices) => (indices, None),
+            None if include_taxonomy_fallback => {
+                let mut result = None;
+                for chain in fetch_taxonomy_chains(id, &mut transaction).await? {
+                    // Prefer the taxonomy term closest to the UUID.
+                    let found = chain.iter().enumerate().find_map(|(distance, term_id)| {
+                        find_indices(&navigation.data, *term_id)
+                            .map(|indices| (distance, indices, *term_id))
+                    });
+                    result = match (result, found) {
+                        (Some(current), Some(found)) if found.0 < current.0 => Some(found),
+                        (None, found) => found,
+                        (current, _) => current,
+                    };
+                }
+                match result {
+                    Some((_, indices, term_id)) => (indices, Some(term_id)),
+                    None => return Err(NavigationPathError::NotFound),
+                }
+            }
+            None => return Err(NavigationPathError::NotFound),
+        };
+
+        transaction.commit().await?;
+
+        let mut path = Vec::with_capacity(indices.len() - 1);
+        let mut subtree = navigation.data;
+        for index in indices[..indices.len() - 1].iter() {
+            let mut ancestor = subtree.into_iter().nth(*index).unwrap();
+            subtree = ancestor.take_children();
+            path.push(ancestor);
+        }
+
+        Ok(NavigationPath {
+            instance,
+            id,
+            taxonomy_term_id,
+            path,
+            subtree,
+        })
+    }
+}
+
+/// Returns the indices leading to the first child (in depth-first order) linking to `id`.
+fn find_indices(children: &[NavigationChild], id: i32) -> Option<Vec<usize>> {
+    children.iter().enumerate().find_map(|(index, child)| {
+        if child.uuid_id() == Some(id) {
+            Some(vec![index])
+        } else {
+            find_indices(child.children(), id).map(|mut indices| {
+                indices.insert(0, index);
+                indices
+            })
+        }
+    })
+}
+
+/// Returns chains of taxonomy terms for the UUID, each starting with a term the UUID is directly
+/// assigned to (or with its parent if the UUID is a taxonomy term itself) followed by its ancestors.
+async fn fetch_taxonomy_chains<'a, E>(id: i32, executor: E) -> Result<Vec<Vec<i32>>, sqlx::Error>
+where
+    E: Executor<'a>,
+{
+    let mut transaction = executor.begin().await?;
+
+    let is_taxonomy_term = sqlx::query!(r#"SELECT id FROM term_taxonomy WHERE id = ?"#, id)
+        .fetch_optional(&mut transaction)
+        .await?
+        .is_some();
+
+    let chains = if is_taxonomy_term {
+        vec![TaxonomyTerm::fetch_ancestors_via_transaction(id, &mut transaction).await?]
+    } else {
+        // Exercises in exercise groups and solutions are linked to the taxonomy via their parents.
+        let terms =
+            Entity::fetch_taxonomy_terms_ancestors_via_transaction(id, &mut transaction).await?;
+
+        let mut chains = Vec::with_capacity(terms.len());
+        for term_id in terms {
+            let mut chain = vec![term_id];
+            chain.extend(
+                TaxonomyTerm::fetch_ancestors_via_transaction(term_id, &mut transaction).await?,
+            );
+            chains.push(chain);
+        }
+        chains
+    };
+
+    transaction.commit().await?;
+
+    Ok(chains)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NavigationPath, NavigationPathError};
+    use crate::create_database_pool;
+    use crate::instance::Instance;
+
+    #[actix_rt::test]
+    async fn navigation_path_of_uuid_in_navigation() {
+        let pool = create_database_pool().await.unwrap();
+
+        // "Mathe Startseite" (19767) is part of the German navigation.
+        let navigation_path = NavigationPath::fetch(Instance::De, 19767, false, &pool)
+            .await
+            .unwrap();
+
+        assert!(navigation_path.taxonomy_term_id.is_none());
+        assert!(navigation_path
+            .subtree
+            .iter()
+            .any(|child| child.uuid_id() == Some(19767)));
+    }
+
+    #[actix_rt::test]
+    async fn navigation_path_falls_back_to_taxonomy_ancestors() {
+        let pool = create_database_pool().await.unwrap();
+
+        // Entity 1855 is not part of the navigation, but its taxonomy terms are.
+        let result = NavigationPath::fetch(Instance::De, 1855, false, &pool).await;
+        assert!(matches!(result, Err(NavigationPathError::NotFound)));
+
+        let navigation_path = NavigationPath::fetch(Instance::De, 1855, true, &pool)
+            .await
+            .unwrap();
+        let taxonomy_term_id = navigation_path.taxonomy_term_id.unwrap();
+        assert!(navigation_path
+            .subtree
+            .iter()
+            .any(|child| child.uuid_id() == Some(taxonomy_term_id)));
+    }
+}
@@ -28,6 +28,14 @@ impl From<sqlx::Error> for NotificationsError {
     }
 }
 
+impl From<SubscriptionsError> for NotificationsError {
+    fn from(error: SubscriptionsError) -> Self {
+        match error {
+            SubscriptionsError::DatabaseError { inner } => inner.into(),
+        }
+    }
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Notification {
@@ -116,22 +124,26 @@ impl Notifications {
         let mut object_ids = vec![object_id];
         object_ids.extend(event.abstract_event.uuid_parameters.values());
 
-        for object_id in object_ids {
-            let subscriptions = Subscriptions::fetch_by_object(object_id, &mut transaction)
-                .await
-                .map_err(|error| match error {
-                    SubscriptionsError::DatabaseError { inner } => NotificationsError::from(inner),
-                })?;
-            let subscriptions = subscriptions
-                .0
-                .iter()
-                .filter(|subscription| subscription.user_id != actor_id);
-            for subscription in subscriptions {
-                subscribers.insert(Subscriber {
-                    user_id: subscription.user_id,
-                    send_email: subscription.send_email,
-                });
-            }
+        let mut subscriptions = Vec::new();
+        for object_id in object_ids.iter() {
+            let direct = Subscriptions::fetch_by_object(*object_id, &mut transaction).await?;
+            subscriptions.extend(direct.0);
+        }
+        // Inherited subscriptions are added last so that a direct subscription takes precedence.
+        for object_id in object_ids.iter() {
+            let inherited =
+                Subscriptions::fetch_inherited_by_object(*object_id, &mut transaction).await?;
+            subscriptions.extend(inherited.0);
+        }
+
+        let subscriptions = subscriptions
+            .iter()
+            .filter(|subscription| subscription.user_id != actor_id);
+        for subscription in subscriptions {
+            subscribers.insert(Subscriber {
+                user_id: subscription.user_id,
+                send_email: subscription.send_email,
+            });
         }
 
         for subscriber in subscribers {
@@ -241,8 +253,10 @@ impl Notifications {
 mod tests {
     use super::{Notifications, SetNotificationStatePayload};
     use crate::create_database_pool;
-    use crate::event::Event;
-    use crate::subscription::Subscriptions;
+    use crate::event::{Event, SetUuidStateEventPayload};
+    use crate::instance::Instance;
+    use crate::subscription::{Subscription, Subscriptions};
+    use crate::uuid::{Entity, TaxonomyTerm};
 
     #[actix_rt::test]
     async fn set_notification_state_no_id() {
@@ -359,6 +373,13 @@ mod tests {
                 .await
                 .unwrap();
         assert!(subscriptions.0.is_empty());
+        let subscriptions = Subscriptions::fetch_inherited_by_object(
+            event.abstract_event.object_id,
+            &mut transaction,
+        )
+        .await
+        .unwrap();
+        assert!(subscriptions.0.is_empty());
 
         Notifications::create_notifications(&event, &mut transaction)
             .await
@@ -480,4 +501,44 @@ mod tests {
             }
         }
     }
+
+    #[actix_rt::test]
+    async fn create_notifications_for_event_with_inherited_subscribers() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        // Subscribe to the root of the taxonomy tree that the entity belongs to.
+        let parents =
+            Entity::fetch_taxonomy_terms_ancestors_via_transaction(1855, &mut transaction)
+                .await
+                .unwrap();
+        let ancestors = TaxonomyTerm::fetch_ancestors_via_transaction(parents[0], &mut transaction)
+            .await
+            .unwrap();
+        let root_id = *ancestors.last().unwrap_or(&parents[0]);
+        Subscription {
+            object_id: root_id,
+            user_id: 10,
+            send_email: false,
+        }
+        .save(&mut transaction)
+        .await
+        .unwrap();
+
+        let event = SetUuidStateEventPayload::new(true, 1, 1855, Instance::De)
+            .save(&mut transaction)
+            .await
+            .unwrap();
+
+        // Verify that the notification was created for the subscriber of the taxonomy term.
+        let notifications = Notifications::fetch_via_transaction(10, &mut transaction)
+            .await
+            .unwrap();
+        let notifications: Vec<_> = notifications
+            .notifications
+            .iter()
+            .filter(|notification| notification.event_id == event.abstract_event.id)
+            .collect();
+        assert_eq!(notifications.len(), 1);
+    }
 }
@@ -0,0 +1,5 @@
+pub use model::*;
+pub use routes::init;
+
+mod model;
+mod routes;
@@ -0,0 +1,315 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use sqlx::MySqlPool;
+use thiserror::Error;
+
+use crate::alias::{AliasError, UuidAliases};
+use crate::database::Executor;
+use crate::datetime::DateTime;
+use crate::instance::Instance;
+
+/// Maximum number of URLs in one sitemap, see https://www.sitemaps.org/protocol.html.
+pub const MAX_URLS_PER_SITEMAP: usize = 50_000;
+
+/// Non-trashed entities (with a current revision), taxonomy terms, pages (with a current revision)
+/// and published blog posts together with the date of their last modification.
+const SITEMAP_UUIDS: &str = r#"
+    FROM uuid u
+    JOIN (
+        SELECT e.id, e.instance_id, r.date AS lastmod
+            FROM entity e
+            JOIN entity_revision r ON r.id = e.current_revision_id
+        UNION ALL
+        SELECT tt.id, t.instance_id, NULL AS lastmod
+            FROM term_taxonomy tt
+            JOIN taxonomy t ON t.id = tt.taxonomy_id
+        UNION ALL
+        SELECT p.id, p.instance_id, r.date AS lastmod
+            FROM page_repository p
+            JOIN page_revision r ON r.id = p.current_revision_id
+        UNION ALL
+        SELECT b.id, b.instance_id, b.date AS lastmod
+            FROM blog_post b
+            WHERE b.publish IS NOT NULL AND b.publish <= NOW()
+    ) s ON s.id = u.id
+    JOIN instance i ON i.id = s.instance_id
+    WHERE i.subdomain = ? AND u.trashed = 0
+"#;
+
+#[derive(Error, Debug)]
+pub enum SitemapError {
+    #[error("Sitemap cannot be fetched because of a database error: {inner:?}.")]
+    DatabaseError { inner: sqlx::Error },
+    #[error("Sitemap cannot be fetched because an alias cannot be fetched: {inner:?}.")]
+    AliasError { inner: AliasError },
+    #[error("Sitemap cannot be fetched because it does not exist.")]
+    NotFound,
+}
+
+impl From<sqlx::Error> for SitemapError {
+    fn from(inner: sqlx::Error) -> Self {
+        SitemapError::DatabaseError { inner }
+    }
+}
+
+impl From<AliasError> for SitemapError {
+    fn from(error: AliasError) -> Self {
+        match error {
+            AliasError::DatabaseError { inner } => inner.into(),
+            inner => SitemapError::AliasError { inner },
+        }
+    }
+}
+
+pub struct SitemapIndex {
+    pub instance: Instance,
+    pub sitemaps: usize,
+}
+
+impl SitemapIndex {
+    pub async fn fetch(instance: Instance, pool: &MySqlPool) -> Result<Self, SitemapError> {
+        Self::fetch_via_transaction(instance, pool).await
+    }
+
+    pub async fn fetch_via_transaction<'a, E>(
+        instance: Instance,
+        executor: E,
+    ) -> Result<Self, SitemapError>
+    where
+        E: Executor<'a>,
+    {
+        let query = format!("SELECT COUNT(*) {}", SITEMAP_UUIDS);
+        let (count,) = sqlx::query_as::<_, (i64,)>(&query)
+            .bind(&instance)
+            .fetch_one(executor)
+            .await?;
+        let count = count as usize;
+
+        // An empty instance still gets one (empty) sitemap.
+        let sitemaps = std::cmp::max(1, (count + MAX_URLS_PER_SITEMAP - 1) / MAX_URLS_PER_SITEMAP);
+
+        Ok(SitemapIndex { instance, sitemaps })
+    }
+
+    pub fn to_xml(&self) -> String {
+        let base_url = get_base_url(&self.instance);
+        let sitemaps: String = (1..=self.sitemaps)
+            .map(|page| {
+                format!(
+                    "<sitemap><loc>{}/sitemap/{}/{}.xml</loc></sitemap>",
+                    base_url,
+                    get_subdomain(&self.instance),
+                    page
+                )
+            })
+            .collect();
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">{}</sitemapindex>"#,
+            sitemaps
+        )
+    }
+}
+
+pub struct Sitemap {
+    pub instance: Instance,
+    pub urls: Vec<SitemapUrl>,
+}
+
+pub struct SitemapUrl {
+    pub id: i32,
+    pub path: String,
+    pub lastmod: Option<DateTime>,
+}
+
+impl Sitemap {
+    pub async fn fetch(
+        instance: Instance,
+        page: usize,
+        pool: &MySqlPool,
+    ) -> Result<Self, SitemapError> {
+        Self::fetch_via_transaction(instance, page, pool).await
+    }
+
+    /// Fetches the `page`-th sitemap (starting at 1) of the instance.
+    pub async fn fetch_via_transaction<'a, E>(
+        instance: Instance,
+        page: usize,
+        executor: E,
+    ) -> Result<Self, SitemapError>
+    where
+        E: Executor<'a>,
+    {
+        if page == 0 {
+            return Err(SitemapError::NotFound);
+        }
+
+        let mut transaction = executor.begin().await?;
+
+        let query = format!(
+            "SELECT u.id, s.lastmod {} ORDER BY u.id LIMIT ? OFFSET ?",
+            SITEMAP_UUIDS
+        );
+        let uuids = sqlx::query_as::<_, (i64, Option<chrono::DateTime<Utc>>)>(&query)
+            .bind(&instance)
+            .bind(MAX_URLS_PER_SITEMAP as i64)
+            .bind(((page - 1) * MAX_URLS_PER_SITEMAP) as i64)
+            .fetch_all(&mut transaction)
+            .await?;
+
+        if uuids.is_empty() && page > 1 {
+            return Err(SitemapError::NotFound);
+        }
+
+        let ids: Vec<i32> = uuids.iter().map(|(id, _)| *id as i32).collect();
+        let aliases = UuidAliases::fetch_via_transaction(&ids, &mut transaction).await?;
+
+        transaction.commit().await?;
+
+        let mut lastmods: HashMap<i32, Option<chrono::DateTime<Utc>>> = uuids
+            .into_iter()
+            .map(|(id, lastmod)| (id as i32, lastmod))
+            .collect();
+        let urls = aliases
+            .aliases
+            .into_iter()
+            .map(|alias| SitemapUrl {
+                id: alias.id,
+                lastmod: lastmods
+                    .remove(&alias.id)
+                    .flatten()
+                    .map(|lastmod| lastmod.into()),
+                path: alias.path,
+            })
+            .collect();
+
+        Ok(Sitemap { instance, urls })
+    }
+
+    pub fn to_xml(&self) -> String {
+        let base_url = get_base_url(&self.instance);
+        let urls: String = self
+            .urls
+            .iter()
+            .map(|url| {
+                let lastmod = url
+                    .lastmod
+                    .as_ref()
+                    .map(|lastmod| format!("<lastmod>{}</lastmod>", lastmod))
+                    .unwrap_or_else(|| "".to_string());
+                format!(
+                    "<url><loc>{}{}</loc>{}</url>",
+                    base_url,
+                    escape_xml(&encode_path(&url.path)),
+                    lastmod
+                )
+            })
+            .collect();
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">{}</urlset>"#,
+            urls
+        )
+    }
+}
+
+fn get_subdomain(instance: &Instance) -> String {
+    serde_json::to_value(instance)
+        .unwrap()
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+fn get_base_url(instance: &Instance) -> String {
+    format!("https://{}.serlo.org", get_subdomain(instance))
+}
+
+/// Percent-encodes all characters of the path that may not appear in a URL as they are (e.g.
+/// umlauts or spaces).
+fn encode_path(path: &str) -> String {
+    path.bytes()
+        .map(|byte| match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' => (byte as char).to_string(),
+            b'-' | b'.' | b'_' | b'~' | b'/' | b'!' | b'$' | b'&' | b'\'' | b'(' | b')' | b'*'
+            | b'+' | b',' | b';' | b'=' | b':' | b'@' | b'%' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Sitemap, SitemapError, SitemapIndex, SitemapUrl};
+    use crate::create_database_pool;
+    use crate::instance::Instance;
+    use crate::uuid::{Uuid, UuidFetcher};
+
+    #[actix_rt::test]
+    async fn sitemap_index() {
+        let pool = create_database_pool().await.unwrap();
+
+        let index = SitemapIndex::fetch(Instance::De, &pool).await.unwrap();
+
+        assert!(index.sitemaps >= 1);
+        assert!(index
+            .to_xml()
+            .contains("<loc>https://de.serlo.org/sitemap/de/1.xml</loc>"));
+    }
+
+    #[actix_rt::test]
+    async fn sitemap() {
+        let pool = create_database_pool().await.unwrap();
+
+        let sitemap = Sitemap::fetch(Instance::De, 1, &pool).await.unwrap();
+
+        assert!(!sitemap.urls.is_empty());
+        assert!(sitemap.urls.iter().all(|url| url.path.starts_with('/')));
+        assert!(sitemap.to_xml().starts_with(r#"<?xml version="1.0""#));
+    }
+
+    #[actix_rt::test]
+    async fn sitemap_locations_match_uuid_alias() {
+        let pool = create_database_pool().await.unwrap();
+
+        let sitemap = Sitemap::fetch(Instance::De, 1, &pool).await.unwrap();
+
+        for url in sitemap.urls.iter().take(20) {
+            let uuid = Uuid::fetch(url.id, &pool).await.unwrap();
+            assert_eq!(url.path, uuid.get_alias(), "Alias of {}", url.id);
+        }
+    }
+
+    #[test]
+    fn sitemap_locations_are_encoded() {
+        let sitemap = Sitemap {
+            instance: Instance::De,
+            urls: vec![SitemapUrl {
+                id: 1,
+                path: "/mathe/größe & maß".to_string(),
+                lastmod: None,
+            }],
+        };
+
+        assert!(sitemap
+            .to_xml()
+            .contains("<loc>https://de.serlo.org/mathe/gr%C3%B6%C3%9Fe%20&amp;%20ma%C3%9F</loc>"));
+    }
+
+    #[actix_rt::test]
+    async fn sitemap_out_of_range() {
+        let pool = create_database_pool().await.unwrap();
+
+        let result = Sitemap::fetch(Instance::De, 1000, &pool).await;
+
+        assert!(matches!(result, Err(SitemapError::NotFound)));
+    }
+}
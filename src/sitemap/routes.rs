@@ -0,0 +1,48 @@
+use actix_web::{get, web, HttpResponse};
+use sqlx::MySqlPool;
+
+use super::model::{Sitemap, SitemapError, SitemapIndex};
+use crate::instance::Instance;
+
+#[get("/sitemap/{instance}.xml")]
+async fn sitemap_index(
+    instance: web::Path<Instance>,
+    db_pool: web::Data<MySqlPool>,
+) -> HttpResponse {
+    let instance = instance.into_inner();
+    match SitemapIndex::fetch(instance.clone(), db_pool.get_ref()).await {
+        Ok(index) => HttpResponse::Ok()
+            .content_type("application/xml; charset=utf-8")
+            .body(index.to_xml()),
+        Err(e) => {
+            println!("/sitemap/{:?}.xml: {:?}", instance, e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[get("/sitemap/{instance}/{page}.xml")]
+async fn sitemap(
+    params: web::Path<(Instance, usize)>,
+    db_pool: web::Data<MySqlPool>,
+) -> HttpResponse {
+    let (instance, page) = params.into_inner();
+    match Sitemap::fetch(instance.clone(), page, db_pool.get_ref()).await {
+        Ok(sitemap) => HttpResponse::Ok()
+            .content_type("application/xml; charset=utf-8")
+            .body(sitemap.to_xml()),
+        Err(e) => {
+            println!("/sitemap/{:?}/{}.xml: {:?}", instance, page, e);
+            match e {
+                SitemapError::DatabaseError { .. } => HttpResponse::InternalServerError().finish(),
+                SitemapError::AliasError { .. } => HttpResponse::InternalServerError().finish(),
+                SitemapError::NotFound => HttpResponse::NotFound().finish(),
+            }
+        }
+    }
+}
+
+pub fn init(cfg: &mut web::ServiceConfig) {
+    cfg.service(sitemap_index);
+    cfg.service(sitemap);
+}
@@ -0,0 +1,437 @@
+//! Generates the slugs used in aliases, see [`crate::format_alias`].
+use crate::instance::Instance;
+
+/// Maximum length of a slug in characters.
+const MAX_LENGTH: usize = 100;
+
+const DEVANAGARI_NUKTA: char = '\u{093C}';
+const DEVANAGARI_VIRAMA: char = '\u{094D}';
+const TAMIL_PULLI: char = '\u{0BCD}';
+
+pub trait SlugGenerator {
+    fn slugify(&self, segment: &str) -> String;
+}
+
+/// Rule-based slug generator. Use [`SlugRules::for_instance`] to get the rules of an instance.
+#[derive(Clone, Debug)]
+pub struct SlugRules {
+    /// Transliterates accented Latin letters, Devanagari and Tamil to ASCII.
+    pub transliterate: bool,
+    /// Replaces ä, ö, ü and ß by ae, oe, ue and ss.
+    pub fold_umlauts: bool,
+    /// Truncates slugs to this number of characters, at a word boundary if possible.
+    pub max_length: Option<usize>,
+}
+
+impl SlugRules {
+    pub fn for_instance(instance: Option<&Instance>) -> Self {
+        match instance {
+            // German aliases keep their umlauts, like the legacy serlo.org aliases did.
+            None | Some(Instance::De) => SlugRules {
+                transliterate: false,
+                fold_umlauts: false,
+                max_length: Some(MAX_LENGTH),
+            },
+            Some(Instance::En) | Some(Instance::Es) | Some(Instance::Fr) | Some(Instance::Hi)
+            | Some(Instance::Ta) => SlugRules {
+                transliterate: true,
+                fold_umlauts: false,
+                max_length: Some(MAX_LENGTH),
+            },
+        }
+    }
+}
+
+impl SlugGenerator for SlugRules {
+    fn slugify(&self, segment: &str) -> String {
+        let segment = segment.to_lowercase();
+        let mut slug = String::with_capacity(segment.len());
+        let mut chars = segment.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if is_removed(c) {
+                continue;
+            }
+            if self.fold_umlauts {
+                if let Some(folded) = fold_umlaut(c) {
+                    slug.push_str(folded);
+                    continue;
+                }
+            }
+            if self.transliterate {
+                if let Some(mut consonant) = transliterate_indic_consonant(c) {
+                    while chars.peek() == Some(&DEVANAGARI_NUKTA) {
+                        chars.next();
+                        consonant = transliterate_nukta_consonant(c).unwrap_or(consonant);
+                    }
+                    slug.push_str(consonant);
+                    match chars.peek().copied() {
+                        Some(DEVANAGARI_VIRAMA) | Some(TAMIL_PULLI) => {
+                            chars.next();
+                        }
+                        Some(next) => match transliterate_indic_vowel_sign(next) {
+                            Some(vowel) => {
+                                slug.push_str(vowel);
+                                chars.next();
+                            }
+                            // Hindi drops the inherent vowel at the end of a word.
+                            None if is_devanagari(c) && !is_devanagari(next) => {}
+                            None => slug.push('a'),
+                        },
+                        None if is_devanagari(c) => {}
+                        None => slug.push('a'),
+                    }
+                    continue;
+                }
+                if let Some(transliterated) = transliterate_char(c) {
+                    slug.push_str(transliterated);
+                    continue;
+                }
+            }
+            if c.is_alphanumeric() || c == '.' || c == '_' || is_indic_mark(c) {
+                slug.push(c);
+            } else {
+                slug.push('-');
+            }
+        }
+
+        let slug = collapse_dashes(&slug);
+        match self.max_length {
+            Some(max_length) => truncate(&slug, max_length),
+            None => slug,
+        }
+    }
+}
+
+fn is_removed(c: char) -> bool {
+    matches!(
+        c,
+        '\'' | '"'
+            | '`'
+            | '´'
+            | '‘'
+            | '’'
+            | '‚'
+            | '“'
+            | '”'
+            | '„'
+            | '«'
+            | '»'
+            | '‹'
+            | '›'
+            | '='
+            | '+'
+            | '*'
+            | '&'
+            | '^'
+            | '%'
+            | '$'
+            | '#'
+            | '@'
+            | '!'
+            | '<'
+            | '>'
+            | '?'
+            | '\u{200C}'
+            | '\u{200D}'
+    )
+}
+
+fn collapse_dashes(slug: &str) -> String {
+    let mut collapsed = String::with_capacity(slug.len());
+    for c in slug.chars() {
+        if c != '-' || !collapsed.ends_with('-') {
+            collapsed.push(c);
+        }
+    }
+    collapsed.trim_matches('-').to_string()
+}
+
+fn truncate(slug: &str, max_length: usize) -> String {
+    if slug.chars().count() <= max_length {
+        return slug.to_string();
+    }
+    let truncated: String = slug.chars().take(max_length).collect();
+    let next = slug.chars().nth(max_length);
+    let truncated = match (next, truncated.rfind('-')) {
+        (Some('-'), _) => truncated.as_str(),
+        (_, Some(index)) if index > 0 => &truncated[..index],
+        _ => truncated.as_str(),
+    };
+    truncated.trim_matches('-').to_string()
+}
+
+fn is_devanagari(c: char) -> bool {
+    ('\u{0900}'..='\u{097F}').contains(&c) && c != '\u{0964}' && c != '\u{0965}'
+}
+
+fn is_tamil(c: char) -> bool {
+    ('\u{0B80}'..='\u{0BFF}').contains(&c)
+}
+
+/// Combining marks (like the virama) are not alphanumeric, but belong to the word.
+fn is_indic_mark(c: char) -> bool {
+    (is_devanagari(c) || is_tamil(c)) && !c.is_alphanumeric()
+}
+
+fn fold_umlaut(c: char) -> Option<&'static str> {
+    match c {
+        'ä' => Some("ae"),
+        'ö' => Some("oe"),
+        'ü' => Some("ue"),
+        'ß' => Some("ss"),
+        _ => None,
+    }
+}
+
+fn transliterate_char(c: char) -> Option<&'static str> {
+    let transliterated = match c {
+        // Latin
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'æ' => "ae",
+        'ç' | 'ć' | 'č' => "c",
+        'ď' | 'đ' => "d",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ę' | 'ě' => "e",
+        'ì' | 'í' | 'î' | 'ï' | 'ī' => "i",
+        'ł' => "l",
+        'ñ' | 'ń' | 'ň' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ő' => "o",
+        'œ' => "oe",
+        'ř' => "r",
+        'ś' | 'š' | 'ş' => "s",
+        'ß' => "ss",
+        'ť' | 'ţ' => "t",
+        'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' => "u",
+        'ý' | 'ÿ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        // Devanagari
+        'अ' => "a",
+        'आ' => "a",
+        'इ' | 'ई' => "i",
+        'उ' | 'ऊ' => "u",
+        'ऋ' => "ri",
+        'ए' | 'ऎ' => "e",
+        'ऐ' => "ai",
+        'ओ' | 'ऒ' => "o",
+        'औ' => "au",
+        'ं' | 'ँ' => "n",
+        'ः' => "h",
+        'ॐ' => "om",
+        '०' => "0",
+        '१' => "1",
+        '२' => "2",
+        '३' => "3",
+        '४' => "4",
+        '५' => "5",
+        '६' => "6",
+        '७' => "7",
+        '८' => "8",
+        '९' => "9",
+        // Tamil
+        'அ' => "a",
+        'ஆ' => "a",
+        'இ' | 'ஈ' => "i",
+        'உ' | 'ஊ' => "u",
+        'எ' | 'ஏ' => "e",
+        'ஐ' => "ai",
+        'ஒ' | 'ஓ' => "o",
+        'ஔ' => "au",
+        'ஃ' => "h",
+        '௦' => "0",
+        '௧' => "1",
+        '௨' => "2",
+        '௩' => "3",
+        '௪' => "4",
+        '௫' => "5",
+        '௬' => "6",
+        '௭' => "7",
+        '௮' => "8",
+        '௯' => "9",
+        // Vowel signs and viramas without a preceding consonant
+        DEVANAGARI_NUKTA | DEVANAGARI_VIRAMA | TAMIL_PULLI => "",
+        c => return transliterate_indic_vowel_sign(c),
+    };
+    Some(transliterated)
+}
+
+fn transliterate_indic_consonant(c: char) -> Option<&'static str> {
+    let transliterated = match c {
+        // Devanagari
+        'क' => "k",
+        'ख' => "kh",
+        'ग' => "g",
+        'घ' => "gh",
+        'ङ' => "n",
+        'च' => "ch",
+        'छ' => "chh",
+        'ज' => "j",
+        'झ' => "jh",
+        'ञ' => "n",
+        'ट' => "t",
+        'ठ' => "th",
+        'ड' => "d",
+        'ढ' => "dh",
+        'ण' => "n",
+        'त' => "t",
+        'थ' => "th",
+        'द' => "d",
+        'ध' => "dh",
+        'न' => "n",
+        'प' => "p",
+        'फ' => "ph",
+        'ब' => "b",
+        'भ' => "bh",
+        'म' => "m",
+        'य' => "y",
+        'र' => "r",
+        'ल' => "l",
+        'व' => "v",
+        'श' | 'ष' => "sh",
+        'स' => "s",
+        'ह' => "h",
+        '\u{0958}' => "q",
+        '\u{0959}' => "kh",
+        '\u{095A}' => "gh",
+        '\u{095B}' => "z",
+        '\u{095C}' => "r",
+        '\u{095D}' => "rh",
+        '\u{095E}' => "f",
+        '\u{095F}' => "y",
+        // Tamil
+        'க' => "k",
+        'ங' => "ng",
+        'ச' => "ch",
+        'ஞ' => "nj",
+        'ட' => "t",
+        'ண' => "n",
+        'த' => "th",
+        'ந' => "n",
+        'ப' => "p",
+        'ம' => "m",
+        'ய' => "y",
+        'ர' => "r",
+        'ல' => "l",
+        'வ' => "v",
+        'ழ' => "zh",
+        'ள' => "l",
+        'ற' => "r",
+        'ன' => "n",
+        'ஜ' => "j",
+        'ஶ' | 'ஷ' => "sh",
+        'ஸ' => "s",
+        'ஹ' => "h",
+        _ => return None,
+    };
+    Some(transliterated)
+}
+
+/// Transliterates a Devanagari consonant followed by a (separate) nukta.
+fn transliterate_nukta_consonant(c: char) -> Option<&'static str> {
+    let transliterated = match c {
+        'क' => "q",
+        'ख' => "kh",
+        'ग' => "gh",
+        'ज' => "z",
+        'ड' => "r",
+        'ढ' => "rh",
+        'फ' => "f",
+        'य' => "y",
+        _ => return None,
+    };
+    Some(transliterated)
+}
+
+fn transliterate_indic_vowel_sign(c: char) -> Option<&'static str> {
+    let transliterated = match c {
+        // Devanagari
+        'ा' => "a",
+        'ि' | 'ी' => "i",
+        'ु' | 'ू' => "u",
+        'ृ' => "ri",
+        'े' | 'ॆ' | 'ॅ' => "e",
+        'ै' => "ai",
+        'ो' | 'ॊ' | 'ॉ' => "o",
+        'ौ' => "au",
+        // Tamil
+        'ா' => "a",
+        'ி' | 'ீ' => "i",
+        'ு' | 'ூ' => "u",
+        'ெ' | 'ே' => "e",
+        'ை' => "ai",
+        'ொ' | 'ோ' => "o",
+        'ௌ' => "au",
+        'ௗ' => "",
+        _ => return None,
+    };
+    Some(transliterated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SlugGenerator, SlugRules};
+    use crate::instance::Instance;
+
+    fn assert_corpus(instance: Instance, corpus: &str) {
+        let rules = SlugRules::for_instance(Some(&instance));
+        for line in corpus.lines().filter(|line| !line.is_empty()) {
+            let mut columns = line.split('\t');
+            let title = columns.next().unwrap();
+            let expected = columns.next().unwrap();
+            assert_eq!(rules.slugify(title), expected, "{:?}: {}", instance, title);
+        }
+    }
+
+    #[test]
+    fn corpus_de() {
+        assert_corpus(Instance::De, include_str!("corpus/de.tsv"));
+    }
+
+    #[test]
+    fn corpus_en() {
+        assert_corpus(Instance::En, include_str!("corpus/en.tsv"));
+    }
+
+    #[test]
+    fn corpus_es() {
+        assert_corpus(Instance::Es, include_str!("corpus/es.tsv"));
+    }
+
+    #[test]
+    fn corpus_fr() {
+        assert_corpus(Instance::Fr, include_str!("corpus/fr.tsv"));
+    }
+
+    #[test]
+    fn corpus_hi() {
+        assert_corpus(Instance::Hi, include_str!("corpus/hi.tsv"));
+    }
+
+    #[test]
+    fn corpus_ta() {
+        assert_corpus(Instance::Ta, include_str!("corpus/ta.tsv"));
+    }
+
+    #[test]
+    fn fold_umlauts() {
+        let rules = SlugRules {
+            transliterate: false,
+            fold_umlauts: true,
+            max_length: None,
+        };
+        assert_eq!(
+            rules.slugify("Größe über Flächen"),
+            "groesse-ueber-flaechen"
+        );
+    }
+
+    #[test]
+    fn truncate_at_word_boundary() {
+        let rules = SlugRules {
+            transliterate: false,
+            fold_umlauts: false,
+            max_length: Some(12),
+        };
+        assert_eq!(rules.slugify("Satz des Pythagoras"), "satz-des");
+        assert_eq!(rules.slugify("Integralrechnung"), "integralrech");
+    }
+}
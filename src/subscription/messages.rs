@@ -3,8 +3,9 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
 use super::model::{
-    Subscription, SubscriptionChangeError, SubscriptionChangePayload, SubscriptionsByUser,
-    SubscriptionsError,
+    AutoSubscribePolicy, AutoSubscribePolicyByUser, SubscriberCounts, Subscription,
+    SubscriptionChangeError, SubscriptionChangePayload, SubscriptionState, SubscriptionsByObject,
+    SubscriptionsByUser, SubscriptionsError,
 };
 use crate::database::Connection;
 use crate::message::MessageResponder;
@@ -13,7 +14,13 @@ use crate::message::MessageResponder;
 #[serde(tag = "type", content = "payload")]
 pub enum SubscriptionMessage {
     SubscriptionsQuery(SubscriptionsQuery),
+    SubscriptionsByObjectQuery(SubscriptionsByObjectQuery),
+    SubscriberCountQuery(SubscriberCountQuery),
+    SubscriptionQuery(SubscriptionQuery),
     SubscriptionSetMutation(SubscriptionSetMutation),
+    SubscriptionUnsubscribeAllMutation(SubscriptionUnsubscribeAllMutation),
+    SubscriptionPolicyQuery(SubscriptionPolicyQuery),
+    SubscriptionSetPolicyMutation(SubscriptionSetPolicyMutation),
 }
 
 #[async_trait]
@@ -21,9 +28,23 @@ impl MessageResponder for SubscriptionMessage {
     async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
         match self {
             SubscriptionMessage::SubscriptionsQuery(message) => message.handle(connection).await,
+            SubscriptionMessage::SubscriptionsByObjectQuery(message) => {
+                message.handle(connection).await
+            }
+            SubscriptionMessage::SubscriberCountQuery(message) => message.handle(connection).await,
+            SubscriptionMessage::SubscriptionQuery(message) => message.handle(connection).await,
             SubscriptionMessage::SubscriptionSetMutation(message) => {
                 message.handle(connection).await
             }
+            SubscriptionMessage::SubscriptionUnsubscribeAllMutation(message) => {
+                message.handle(connection).await
+            }
+            SubscriptionMessage::SubscriptionPolicyQuery(message) => {
+                message.handle(connection).await
+            }
+            SubscriptionMessage::SubscriptionSetPolicyMutation(message) => {
+                message.handle(connection).await
+            }
         }
     }
 }
@@ -59,6 +80,103 @@ impl MessageResponder for SubscriptionsQuery {
     }
 }
 
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionsByObjectQuery {
+    pub object_id: i32,
+}
+
+#[async_trait]
+impl MessageResponder for SubscriptionsByObjectQuery {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let subscriptions = match connection {
+            Connection::Pool(pool) => SubscriptionsByObject::fetch(self.object_id, pool).await,
+            Connection::Transaction(transaction) => {
+                SubscriptionsByObject::fetch_via_transaction(self.object_id, transaction).await
+            }
+        };
+        match subscriptions {
+            Ok(data) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(data),
+            Err(e) => {
+                println!("/subscribers/{}: {:?}", self.object_id, e);
+                match e {
+                    SubscriptionsError::DatabaseError { .. } => {
+                        HttpResponse::InternalServerError().finish()
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriberCountQuery {
+    pub ids: Vec<i32>,
+}
+
+#[async_trait]
+impl MessageResponder for SubscriberCountQuery {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let counts = match connection {
+            Connection::Pool(pool) => SubscriberCounts::fetch(&self.ids, pool).await,
+            Connection::Transaction(transaction) => {
+                SubscriberCounts::fetch_via_transaction(&self.ids, transaction).await
+            }
+        };
+        match counts {
+            Ok(data) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(data),
+            Err(e) => {
+                println!("/subscriber-count: {:?}", e);
+                match e {
+                    SubscriptionsError::DatabaseError { .. } => {
+                        HttpResponse::InternalServerError().finish()
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionQuery {
+    pub user_id: i32,
+    pub object_id: i32,
+}
+
+#[async_trait]
+impl MessageResponder for SubscriptionQuery {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let subscription = match connection {
+            Connection::Pool(pool) => {
+                SubscriptionState::fetch(self.user_id, self.object_id, pool).await
+            }
+            Connection::Transaction(transaction) => {
+                SubscriptionState::fetch_via_transaction(self.user_id, self.object_id, transaction)
+                    .await
+            }
+        };
+        match subscription {
+            Ok(data) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(data),
+            Err(e) => {
+                println!("/subscription/{}/{}: {:?}", self.user_id, self.object_id, e);
+                match e {
+                    SubscriptionsError::DatabaseError { .. } => {
+                        HttpResponse::InternalServerError().finish()
+                    }
+                }
+            }
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SubscriptionSetMutation {
@@ -83,6 +201,100 @@ impl MessageResponder for SubscriptionSetMutation {
                 Subscription::change_subscription(payload, transaction).await
             }
         };
+        match response {
+            Ok(data) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(data),
+            Err(e) => {
+                println!("{:?}: {:?}", self, e);
+                match e {
+                    SubscriptionChangeError::DatabaseError { .. } => {
+                        HttpResponse::InternalServerError().finish()
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionUnsubscribeAllMutation {
+    pub user_id: i32,
+}
+
+#[async_trait]
+impl MessageResponder for SubscriptionUnsubscribeAllMutation {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let response = match connection {
+            Connection::Pool(pool) => Subscription::unsubscribe_all(self.user_id, pool).await,
+            Connection::Transaction(transaction) => {
+                Subscription::unsubscribe_all(self.user_id, transaction).await
+            }
+        };
+        match response {
+            Ok(data) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(data),
+            Err(e) => {
+                println!("{:?}: {:?}", self, e);
+                match e {
+                    SubscriptionChangeError::DatabaseError { .. } => {
+                        HttpResponse::InternalServerError().finish()
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionPolicyQuery {
+    pub user_id: i32,
+}
+
+#[async_trait]
+impl MessageResponder for SubscriptionPolicyQuery {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let policy = match connection {
+            Connection::Pool(pool) => AutoSubscribePolicyByUser::fetch(self.user_id, pool).await,
+            Connection::Transaction(transaction) => {
+                AutoSubscribePolicyByUser::fetch_via_transaction(self.user_id, transaction).await
+            }
+        };
+        match policy {
+            Ok(data) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(data),
+            Err(e) => {
+                println!("/subscription-policy/{}: {:?}", self.user_id, e);
+                match e {
+                    SubscriptionsError::DatabaseError { .. } => {
+                        HttpResponse::InternalServerError().finish()
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionSetPolicyMutation {
+    pub user_id: i32,
+    pub policy: AutoSubscribePolicy,
+}
+
+#[async_trait]
+impl MessageResponder for SubscriptionSetPolicyMutation {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let response = match connection {
+            Connection::Pool(pool) => self.policy.save(self.user_id, pool).await,
+            Connection::Transaction(transaction) => {
+                self.policy.save(self.user_id, transaction).await
+            }
+        };
         match response {
             Ok(_) => HttpResponse::Ok().finish(),
             Err(e) => {
@@ -1,9 +1,12 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 use sqlx::MySqlPool;
 use thiserror::Error;
 
-use crate::database::Executor;
+use crate::database::{placeholders, Executor};
 use crate::datetime::DateTime;
+use crate::uuid::{Entity, TaxonomyTerm};
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct Subscriptions(pub Vec<Subscription>);
@@ -76,9 +79,98 @@ impl Subscriptions {
 
         Ok(Subscriptions(subscriptions))
     }
+
+    /// Fetches the subscriptions that the given object inherits from the taxonomy terms above it.
+    ///
+    /// Subscribing to a taxonomy term also subscribes to all entities linked anywhere below that
+    /// term, including their revisions and threads. Objects that don't belong to an entity don't
+    /// inherit any subscriptions.
+    pub async fn fetch_inherited_by_object<'a, E>(
+        object_id: i32,
+        executor: E,
+    ) -> Result<Self, SubscriptionsError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        let entity = sqlx::query!(
+            r#"
+                SELECT COALESCE(e.id, r.repository_id) as entity_id
+                    FROM (
+                        SELECT COALESCE(p.uuid_id, c.uuid_id, u.id) as id
+                            FROM uuid u
+                            LEFT JOIN comment c ON c.id = u.id
+                            LEFT JOIN comment p ON p.id = c.parent_id
+                            WHERE u.id = ?
+                    ) o
+                    LEFT JOIN entity e ON e.id = o.id
+                    LEFT JOIN entity_revision r ON r.id = o.id
+            "#,
+            object_id
+        )
+        .fetch_optional(&mut transaction)
+        .await?;
+
+        let mut taxonomy_term_ids: Vec<i32> = Vec::new();
+        if let Some(entity_id) = entity.and_then(|entity| entity.entity_id) {
+            let parents = Entity::fetch_taxonomy_terms_ancestors_via_transaction(
+                entity_id as i32,
+                &mut transaction,
+            )
+            .await?;
+            for parent_id in parents {
+                let ancestors =
+                    TaxonomyTerm::fetch_ancestors_via_transaction(parent_id, &mut transaction)
+                        .await?;
+                for id in std::iter::once(parent_id).chain(ancestors) {
+                    if !taxonomy_term_ids.contains(&id) {
+                        taxonomy_term_ids.push(id);
+                    }
+                }
+            }
+        }
+
+        let mut subscriptions = Vec::new();
+        for taxonomy_term_id in taxonomy_term_ids {
+            let inherited = Self::fetch_by_object(taxonomy_term_id, &mut transaction).await?;
+            subscriptions.extend(inherited.0);
+        }
+
+        transaction.commit().await?;
+
+        Ok(Subscriptions(subscriptions))
+    }
 }
 
 impl Subscription {
+    pub async fn fetch_by_user_and_object<'a, E>(
+        user_id: i32,
+        object_id: i32,
+        executor: E,
+    ) -> Result<Option<Self>, SubscriptionsError>
+    where
+        E: Executor<'a>,
+    {
+        let subscription = sqlx::query!(
+            r#"
+                SELECT uuid_id, user_id, notify_mailman
+                    FROM subscription
+                    WHERE user_id = ? AND uuid_id = ?
+            "#,
+            user_id,
+            object_id
+        )
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(subscription.map(|subscription| Subscription {
+            object_id: subscription.uuid_id as i32,
+            user_id: subscription.user_id as i32,
+            send_email: subscription.notify_mailman != 0,
+        }))
+    }
+
     pub async fn save<'a, E>(&self, executor: E) -> Result<(), SubscriptionChangeError>
     where
         E: Executor<'a>,
@@ -123,6 +215,158 @@ impl Subscription {
     }
 }
 
+/// Describes whether a user gets subscribed to the objects they author (e.g. when they create an
+/// entity, add or review a revision, create a taxonomy term or start a thread).
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AutoSubscribePolicy {
+    /// The user is always subscribed.
+    Always,
+    /// The user is never subscribed.
+    Never,
+    /// The user decides for each action, i.e. the `subscribe` flag of the mutation is respected.
+    Ask,
+}
+
+impl Default for AutoSubscribePolicy {
+    fn default() -> Self {
+        AutoSubscribePolicy::Ask
+    }
+}
+
+impl std::str::FromStr for AutoSubscribePolicy {
+    type Err = serde_json::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_value(serde_json::value::Value::String(s.to_string()))
+    }
+}
+
+impl AutoSubscribePolicy {
+    pub async fn fetch<'a, E>(user_id: i32, executor: E) -> Result<Self, SubscriptionsError>
+    where
+        E: Executor<'a>,
+    {
+        let field = sqlx::query!(
+            r#"SELECT value FROM user_field WHERE user_id = ? AND field = 'auto_subscribe'"#,
+            user_id
+        )
+        .fetch_optional(executor)
+        .await?;
+
+        // Users that never changed their settings (or have an unknown value stored) are asked.
+        Ok(field
+            .and_then(|field| field.value.parse().ok())
+            .unwrap_or_default())
+    }
+
+    pub async fn save<'a, E>(
+        &self,
+        user_id: i32,
+        executor: E,
+    ) -> Result<(), SubscriptionChangeError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        let value = serde_json::to_value(self).unwrap();
+        let value = value.as_str().unwrap();
+
+        sqlx::query!(
+            r#"DELETE FROM user_field WHERE user_id = ? AND field = 'auto_subscribe'"#,
+            user_id
+        )
+        .execute(&mut transaction)
+        .await?;
+        sqlx::query!(
+            r#"
+                INSERT INTO user_field (user_id, field, value)
+                    VALUES (?, 'auto_subscribe', ?)
+            "#,
+            user_id,
+            value
+        )
+        .execute(&mut transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+}
+
+impl Subscription {
+    /// Subscribes the author of an authoring action to the given objects according to their
+    /// [`AutoSubscribePolicy`]. `subscribe` is the choice the user made for this action, which is
+    /// only respected if the policy is [`AutoSubscribePolicy::Ask`].
+    ///
+    /// Every mutation that creates or reviews content should use this instead of calling
+    /// [`Subscription::save`] directly.
+    pub async fn auto_subscribe<'a, E>(
+        object_ids: &[i32],
+        user_id: i32,
+        subscribe: bool,
+        send_email: bool,
+        executor: E,
+    ) -> Result<(), SubscriptionChangeError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        let policy = AutoSubscribePolicy::fetch(user_id, &mut transaction)
+            .await
+            .map_err(|error| match error {
+                SubscriptionsError::DatabaseError { inner } => SubscriptionChangeError::from(inner),
+            })?;
+        let subscribe = match policy {
+            AutoSubscribePolicy::Always => true,
+            AutoSubscribePolicy::Never => false,
+            AutoSubscribePolicy::Ask => subscribe,
+        };
+
+        if subscribe {
+            for object_id in object_ids {
+                let subscription = Subscription {
+                    object_id: *object_id,
+                    user_id,
+                    send_email,
+                };
+                subscription.save(&mut transaction).await?;
+            }
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoSubscribePolicyByUser {
+    user_id: i32,
+    policy: AutoSubscribePolicy,
+}
+
+impl AutoSubscribePolicyByUser {
+    pub async fn fetch(user_id: i32, pool: &MySqlPool) -> Result<Self, SubscriptionsError> {
+        Self::fetch_via_transaction(user_id, pool).await
+    }
+
+    pub async fn fetch_via_transaction<'a, E>(
+        user_id: i32,
+        executor: E,
+    ) -> Result<Self, SubscriptionsError>
+    where
+        E: Executor<'a>,
+    {
+        let policy = AutoSubscribePolicy::fetch(user_id, executor).await?;
+        Ok(AutoSubscribePolicyByUser { user_id, policy })
+    }
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SubscriptionsByUser {
@@ -165,6 +409,146 @@ impl SubscriptionsByUser {
     }
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionsByObject {
+    object_id: i32,
+    subscribers: Vec<SubscriptionByObject>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionByObject {
+    user_id: i32,
+    send_email: bool,
+}
+
+impl SubscriptionsByObject {
+    pub async fn fetch(object_id: i32, pool: &MySqlPool) -> Result<Self, SubscriptionsError> {
+        Self::fetch_via_transaction(object_id, pool).await
+    }
+
+    pub async fn fetch_via_transaction<'a, E>(
+        object_id: i32,
+        executor: E,
+    ) -> Result<Self, SubscriptionsError>
+    where
+        E: Executor<'a>,
+    {
+        let subscriptions = Subscriptions::fetch_by_object(object_id, executor).await?;
+        let subscribers = subscriptions
+            .0
+            .iter()
+            .map(|child| SubscriptionByObject {
+                user_id: child.user_id,
+                send_email: child.send_email,
+            })
+            .collect();
+
+        Ok(SubscriptionsByObject {
+            object_id,
+            subscribers,
+        })
+    }
+}
+
+#[derive(Serialize)]
+pub struct SubscriberCounts {
+    counts: Vec<SubscriberCount>,
+}
+
+#[derive(Serialize)]
+pub struct SubscriberCount {
+    id: i32,
+    count: i32,
+}
+
+impl SubscriberCounts {
+    pub async fn fetch(ids: &[i32], pool: &MySqlPool) -> Result<Self, SubscriptionsError> {
+        Self::fetch_via_transaction(ids, pool).await
+    }
+
+    pub async fn fetch_via_transaction<'a, E>(
+        ids: &[i32],
+        executor: E,
+    ) -> Result<Self, SubscriptionsError>
+    where
+        E: Executor<'a>,
+    {
+        if ids.is_empty() {
+            return Ok(SubscriberCounts { counts: Vec::new() });
+        }
+
+        let query = format!(
+            r#"
+                SELECT uuid_id, COUNT(*)
+                    FROM subscription
+                    WHERE uuid_id IN ({})
+                    GROUP BY uuid_id
+            "#,
+            placeholders(ids.len())
+        );
+        let mut query = sqlx::query_as::<_, (i64, i64)>(&query);
+        for id in ids {
+            query = query.bind(id);
+        }
+        let counts: HashMap<i32, i32> = query
+            .fetch_all(executor)
+            .await?
+            .into_iter()
+            .map(|(id, count)| (id as i32, count as i32))
+            .collect();
+
+        let counts = ids
+            .iter()
+            .map(|id| SubscriberCount {
+                id: *id,
+                count: counts.get(id).copied().unwrap_or(0),
+            })
+            .collect();
+
+        Ok(SubscriberCounts { counts })
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionState {
+    user_id: i32,
+    object_id: i32,
+    subscribed: bool,
+    send_email: bool,
+}
+
+impl SubscriptionState {
+    pub async fn fetch(
+        user_id: i32,
+        object_id: i32,
+        pool: &MySqlPool,
+    ) -> Result<Self, SubscriptionsError> {
+        Self::fetch_via_transaction(user_id, object_id, pool).await
+    }
+
+    pub async fn fetch_via_transaction<'a, E>(
+        user_id: i32,
+        object_id: i32,
+        executor: E,
+    ) -> Result<Self, SubscriptionsError>
+    where
+        E: Executor<'a>,
+    {
+        let subscription =
+            Subscription::fetch_by_user_and_object(user_id, object_id, executor).await?;
+
+        Ok(SubscriptionState {
+            user_id,
+            object_id,
+            subscribed: subscription.is_some(),
+            send_email: subscription.map_or(false, |subscription| subscription.send_email),
+        })
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SubscriptionChangePayload {
@@ -186,38 +570,182 @@ impl From<sqlx::Error> for SubscriptionChangeError {
     }
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionChangeResponse {
+    subscriptions: Vec<SubscriptionState>,
+    invalid_ids: Vec<i32>,
+}
+
 impl Subscription {
+    /// Subscribes or unsubscribes the user to all given objects at once.
+    ///
+    /// Ids that don't exist (or belong to a user) are skipped and reported as `invalidIds`. For
+    /// all other ids, the resulting subscription state is returned.
     pub async fn change_subscription<'a, E>(
         payload: SubscriptionChangePayload,
         executor: E,
-    ) -> Result<(), SubscriptionChangeError>
+    ) -> Result<SubscriptionChangeResponse, SubscriptionChangeError>
     where
         E: Executor<'a>,
     {
+        let mut ids = payload.ids;
+        ids.sort_unstable();
+        ids.dedup();
+
+        if ids.is_empty() {
+            return Ok(SubscriptionChangeResponse {
+                subscriptions: Vec::new(),
+                invalid_ids: Vec::new(),
+            });
+        }
+
         let mut transaction = executor.begin().await?;
 
-        for id in payload.ids.into_iter() {
-            let subscription = Subscription {
-                object_id: id,
-                user_id: payload.user_id,
-                send_email: payload.send_email,
-            };
+        let query = format!(
+            r#"SELECT id FROM uuid WHERE discriminator != 'user' AND id IN ({})"#,
+            placeholders(ids.len())
+        );
+        let mut query = sqlx::query_as::<_, (i64,)>(&query);
+        for id in ids.iter() {
+            query = query.bind(id);
+        }
+        let valid_ids: Vec<i32> = query
+            .fetch_all(&mut transaction)
+            .await?
+            .into_iter()
+            .map(|(id,)| id as i32)
+            .collect();
+        let invalid_ids = ids
+            .into_iter()
+            .filter(|id| !valid_ids.contains(id))
+            .collect();
 
+        if !valid_ids.is_empty() {
             if payload.subscribe {
-                subscription.save(&mut transaction).await?;
+                let query = format!(
+                    r#"
+                        INSERT INTO subscription (uuid_id, user_id, notify_mailman, date)
+                            VALUES {}
+                            ON DUPLICATE KEY UPDATE notify_mailman = VALUES(notify_mailman)
+                    "#,
+                    vec!["(?, ?, ?, ?)"; valid_ids.len()].join(", ")
+                );
+                let mut query = sqlx::query(&query);
+                for id in valid_ids.iter() {
+                    query = query
+                        .bind(id)
+                        .bind(payload.user_id)
+                        .bind(payload.send_email)
+                        .bind(DateTime::now());
+                }
+                query.execute(&mut transaction).await?;
             } else {
-                subscription.remove(&mut transaction).await?;
+                let query = format!(
+                    r#"DELETE FROM subscription WHERE user_id = ? AND uuid_id IN ({})"#,
+                    placeholders(valid_ids.len())
+                );
+                let mut query = sqlx::query(&query).bind(payload.user_id);
+                for id in valid_ids.iter() {
+                    query = query.bind(id);
+                }
+                query.execute(&mut transaction).await?;
             }
         }
+
+        let subscriptions =
+            Self::fetch_states(payload.user_id, &valid_ids, &mut transaction).await?;
+
         transaction.commit().await?;
 
-        Ok(())
+        Ok(SubscriptionChangeResponse {
+            subscriptions,
+            invalid_ids,
+        })
+    }
+
+    /// Removes all subscriptions of the user, e.g. when their account is deleted.
+    pub async fn unsubscribe_all<'a, E>(
+        user_id: i32,
+        executor: E,
+    ) -> Result<SubscriptionChangeResponse, SubscriptionChangeError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        let subscriptions = Subscriptions::fetch_by_user(user_id, &mut transaction)
+            .await
+            .map_err(|error| match error {
+                SubscriptionsError::DatabaseError { inner } => SubscriptionChangeError::from(inner),
+            })?;
+
+        sqlx::query!(r#"DELETE FROM subscription WHERE user_id = ?"#, user_id)
+            .execute(&mut transaction)
+            .await?;
+
+        transaction.commit().await?;
+
+        Ok(SubscriptionChangeResponse {
+            subscriptions: subscriptions
+                .0
+                .iter()
+                .map(|subscription| SubscriptionState {
+                    user_id,
+                    object_id: subscription.object_id,
+                    subscribed: false,
+                    send_email: false,
+                })
+                .collect(),
+            invalid_ids: Vec::new(),
+        })
+    }
+
+    async fn fetch_states<'a, E>(
+        user_id: i32,
+        ids: &[i32],
+        executor: E,
+    ) -> Result<Vec<SubscriptionState>, sqlx::Error>
+    where
+        E: Executor<'a>,
+    {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let query = format!(
+            r#"SELECT uuid_id, notify_mailman FROM subscription WHERE user_id = ? AND uuid_id IN ({})"#,
+            placeholders(ids.len())
+        );
+        let mut query = sqlx::query_as::<_, (i64, i8)>(&query).bind(user_id);
+        for id in ids {
+            query = query.bind(id);
+        }
+        let subscriptions: HashMap<i32, bool> = query
+            .fetch_all(executor)
+            .await?
+            .into_iter()
+            .map(|(id, send_email)| (id as i32, send_email != 0))
+            .collect();
+
+        Ok(ids
+            .iter()
+            .map(|id| SubscriptionState {
+                user_id,
+                object_id: *id,
+                subscribed: subscriptions.contains_key(id),
+                send_email: subscriptions.get(id).copied().unwrap_or(false),
+            })
+            .collect())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Subscription, Subscriptions, SubscriptionsError};
+    use super::{
+        AutoSubscribePolicy, SubscriberCounts, Subscription, SubscriptionChangePayload,
+        SubscriptionState, Subscriptions, SubscriptionsError,
+    };
     use crate::create_database_pool;
     use crate::database::Executor;
 
@@ -304,6 +832,160 @@ mod tests {
         assert!(subscription.is_none());
     }
 
+    #[actix_rt::test]
+    async fn fetch_subscriber_counts() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        let subscriptions = Subscriptions::fetch_by_object(1565, &mut transaction)
+            .await
+            .unwrap();
+
+        let counts = SubscriberCounts::fetch_via_transaction(&[1565, 999999], &mut transaction)
+            .await
+            .unwrap();
+        let counts = serde_json::to_value(counts).unwrap();
+
+        assert_eq!(
+            counts,
+            serde_json::json!({
+                "counts": [
+                    { "id": 1565, "count": subscriptions.0.len() },
+                    { "id": 999999, "count": 0 }
+                ]
+            })
+        );
+    }
+
+    #[actix_rt::test]
+    async fn fetch_subscription_state() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        let subscribed = SubscriptionState::fetch_via_transaction(1, 1565, &mut transaction)
+            .await
+            .unwrap();
+        assert!(subscribed.subscribed);
+
+        let not_subscribed = SubscriptionState::fetch_via_transaction(1, 1555, &mut transaction)
+            .await
+            .unwrap();
+        assert!(!not_subscribed.subscribed);
+        assert!(!not_subscribed.send_email);
+    }
+
+    #[actix_rt::test]
+    async fn auto_subscribe_respects_policy() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        // Verify assumption that the user is not subscribed to object
+        let subscription = fetch_subscription_by_user_and_object(1, 1555, &mut transaction)
+            .await
+            .unwrap();
+        assert!(subscription.is_none());
+
+        AutoSubscribePolicy::Never
+            .save(1, &mut transaction)
+            .await
+            .unwrap();
+        Subscription::auto_subscribe(&[1555], 1, true, false, &mut transaction)
+            .await
+            .unwrap();
+        let subscription = fetch_subscription_by_user_and_object(1, 1555, &mut transaction)
+            .await
+            .unwrap();
+        assert!(subscription.is_none());
+
+        AutoSubscribePolicy::Always
+            .save(1, &mut transaction)
+            .await
+            .unwrap();
+        Subscription::auto_subscribe(&[1555], 1, false, false, &mut transaction)
+            .await
+            .unwrap();
+        let subscription = fetch_subscription_by_user_and_object(1, 1555, &mut transaction)
+            .await
+            .unwrap();
+        assert!(subscription.is_some());
+    }
+
+    #[actix_rt::test]
+    async fn change_subscription_reports_invalid_ids() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        let response = Subscription::change_subscription(
+            SubscriptionChangePayload {
+                ids: vec![1555, 1565, 1, 999999],
+                user_id: 1,
+                subscribe: true,
+                send_email: true,
+            },
+            &mut transaction,
+        )
+        .await
+        .unwrap();
+        let response = serde_json::to_value(response).unwrap();
+
+        assert_eq!(
+            response,
+            serde_json::json!({
+                "subscriptions": [
+                    { "userId": 1, "objectId": 1555, "subscribed": true, "sendEmail": true },
+                    { "userId": 1, "objectId": 1565, "subscribed": true, "sendEmail": true }
+                ],
+                "invalidIds": [1, 999999]
+            })
+        );
+    }
+
+    #[actix_rt::test]
+    async fn change_subscription_unsubscribe() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        Subscription::change_subscription(
+            SubscriptionChangePayload {
+                ids: vec![1565],
+                user_id: 1,
+                subscribe: false,
+                send_email: false,
+            },
+            &mut transaction,
+        )
+        .await
+        .unwrap();
+
+        // Verify that user is no longer subscribed to object
+        let subscription = fetch_subscription_by_user_and_object(1, 1565, &mut transaction)
+            .await
+            .unwrap();
+        assert!(subscription.is_none());
+    }
+
+    #[actix_rt::test]
+    async fn unsubscribe_all() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        // Verify assumption that the user has subscriptions
+        let subscriptions = Subscriptions::fetch_by_user(1, &mut transaction)
+            .await
+            .unwrap();
+        assert!(!subscriptions.0.is_empty());
+
+        Subscription::unsubscribe_all(1, &mut transaction)
+            .await
+            .unwrap();
+
+        // Verify that user has no subscriptions anymore
+        let subscriptions = Subscriptions::fetch_by_user(1, &mut transaction)
+            .await
+            .unwrap();
+        assert!(subscriptions.0.is_empty());
+    }
+
     async fn fetch_subscription_by_user_and_object<'a, E>(
         user_id: i32,
         object_id: i32,
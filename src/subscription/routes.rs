@@ -1,7 +1,7 @@
 use actix_web::{get, web, Responder};
 use sqlx::MySqlPool;
 
-use super::messages::SubscriptionsQuery;
+use super::messages::{SubscriptionQuery, SubscriptionsByObjectQuery, SubscriptionsQuery};
 use crate::database::Connection;
 use crate::message::MessageResponder;
 
@@ -13,6 +13,27 @@ async fn subscriptions(user_id: web::Path<i32>, db_pool: web::Data<MySqlPool>) -
     message.handle(connection).await
 }
 
+#[get("/subscribers/{object_id}")]
+async fn subscribers(object_id: web::Path<i32>, db_pool: web::Data<MySqlPool>) -> impl Responder {
+    let object_id = object_id.into_inner();
+    let message = SubscriptionsByObjectQuery { object_id };
+    let connection = Connection::Pool(db_pool.get_ref());
+    message.handle(connection).await
+}
+
+#[get("/subscription/{user_id}/{object_id}")]
+async fn subscription(
+    params: web::Path<(i32, i32)>,
+    db_pool: web::Data<MySqlPool>,
+) -> impl Responder {
+    let (user_id, object_id) = params.into_inner();
+    let message = SubscriptionQuery { user_id, object_id };
+    let connection = Connection::Pool(db_pool.get_ref());
+    message.handle(connection).await
+}
+
 pub fn init(cfg: &mut web::ServiceConfig) {
     cfg.service(subscriptions);
+    cfg.service(subscribers);
+    cfg.service(subscription);
 }
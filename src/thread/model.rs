@@ -246,21 +246,17 @@ impl Threads {
         .save(&mut transaction)
         .await?;
 
-        if payload.subscribe {
-            for object_id in [payload.thread_id, comment_id].iter() {
-                let subscription = Subscription {
-                    object_id: *object_id,
-                    user_id: payload.user_id,
-                    send_email: payload.send_email,
-                };
-                subscription
-                    .save(&mut transaction)
-                    .await
-                    .map_err(|error| match error {
-                        SubscriptionChangeError::DatabaseError { inner } => EventError::from(inner),
-                    })?;
-            }
-        }
+        Subscription::auto_subscribe(
+            &[payload.thread_id, comment_id],
+            payload.user_id,
+            payload.subscribe,
+            payload.send_email,
+            &mut transaction,
+        )
+        .await
+        .map_err(|error| match error {
+            SubscriptionChangeError::DatabaseError { inner } => EventError::from(inner),
+        })?;
 
         let comment = Uuid::fetch_via_transaction(comment_id, &mut transaction).await?;
 
@@ -388,19 +384,17 @@ impl Threads {
             .save(&mut transaction)
             .await?;
 
-        if payload.subscribe {
-            let subscription = Subscription {
-                object_id: thread_id,
-                user_id: payload.user_id,
-                send_email: payload.send_email,
-            };
-            subscription
-                .save(&mut transaction)
-                .await
-                .map_err(|error| match error {
-                    SubscriptionChangeError::DatabaseError { inner } => EventError::from(inner),
-                })?;
-        }
+        Subscription::auto_subscribe(
+            &[thread_id],
+            payload.user_id,
+            payload.subscribe,
+            payload.send_email,
+            &mut transaction,
+        )
+        .await
+        .map_err(|error| match error {
+            SubscriptionChangeError::DatabaseError { inner } => EventError::from(inner),
+        })?;
 
         let comment = Uuid::fetch_via_transaction(thread_id, &mut transaction).await?;
 
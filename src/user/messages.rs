@@ -2,8 +2,9 @@ use actix_web::HttpResponse;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
-use super::model::{User, UserError};
+use super::model::{ActiveUser, ActivityCriteria, User, UserActivityStats, UserError};
 use crate::database::Connection;
+use crate::datetime::DateTime;
 use crate::message::MessageResponder;
 
 #[derive(Deserialize, Serialize)]
@@ -11,6 +12,7 @@ use crate::message::MessageResponder;
 pub enum UserMessage {
     ActiveAuthorsQuery(ActiveAuthorsQuery),
     ActiveReviewersQuery(ActiveReviewersQuery),
+    UserActivityStatsQuery(UserActivityStatsQuery),
 }
 
 #[async_trait]
@@ -19,58 +21,116 @@ impl MessageResponder for UserMessage {
         match self {
             UserMessage::ActiveAuthorsQuery(message) => message.handle(connection).await,
             UserMessage::ActiveReviewersQuery(message) => message.handle(connection).await,
+            UserMessage::UserActivityStatsQuery(message) => message.handle(connection).await,
         }
     }
 }
 
-#[derive(Deserialize, Serialize)]
+fn active_users_response(active_users: Vec<ActiveUser>, include_counts: bool) -> HttpResponse {
+    let mut response = HttpResponse::Ok();
+    response.content_type("application/json; charset=utf-8");
+    if include_counts {
+        response.json(active_users)
+    } else {
+        let ids: Vec<i32> = active_users.iter().map(|user| user.id).collect();
+        response.json(ids)
+    }
+}
+
+#[derive(Default, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
-pub struct ActiveAuthorsQuery {}
+pub struct ActiveAuthorsQuery {
+    #[serde(flatten)]
+    pub criteria: ActivityCriteria,
+    /// Whether the number of counted events should be returned alongside the ids of the users.
+    /// Otherwise, only the ids are returned.
+    #[serde(default)]
+    pub include_counts: bool,
+}
 
 #[async_trait]
 impl MessageResponder for ActiveAuthorsQuery {
     async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
         let active_authors = match connection {
-            Connection::Pool(pool) => User::fetch_active_authors(pool).await,
+            Connection::Pool(pool) => User::fetch_active_authors(&self.criteria, pool).await,
             Connection::Transaction(transaction) => {
-                User::fetch_active_authors_via_transaction(transaction).await
+                User::fetch_active_authors_via_transaction(&self.criteria, transaction).await
             }
         };
         match active_authors {
-            Ok(data) => HttpResponse::Ok()
-                .content_type("application/json; charset=utf-8")
-                .json(data),
+            Ok(data) => active_users_response(data, self.include_counts),
             Err(e) => {
                 println!("/user/active-authors: {:?}", e);
                 match e {
                     UserError::DatabaseError { .. } => HttpResponse::InternalServerError().finish(),
+                    UserError::InvalidInstance => HttpResponse::InternalServerError().finish(),
                 }
             }
         }
     }
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Default, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
-pub struct ActiveReviewersQuery {}
+pub struct ActiveReviewersQuery {
+    #[serde(flatten)]
+    pub criteria: ActivityCriteria,
+    /// Whether the number of counted events should be returned alongside the ids of the users.
+    /// Otherwise, only the ids are returned.
+    #[serde(default)]
+    pub include_counts: bool,
+}
 
 #[async_trait]
 impl MessageResponder for ActiveReviewersQuery {
     async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
         let active_reviewers = match connection {
-            Connection::Pool(pool) => User::fetch_active_reviewers(pool).await,
+            Connection::Pool(pool) => User::fetch_active_reviewers(&self.criteria, pool).await,
             Connection::Transaction(transaction) => {
-                User::fetch_active_reviewers_via_transaction(transaction).await
+                User::fetch_active_reviewers_via_transaction(&self.criteria, transaction).await
             }
         };
         match active_reviewers {
+            Ok(data) => active_users_response(data, self.include_counts),
+            Err(e) => {
+                println!("/user/active-reviewers: {:?}", e);
+                match e {
+                    UserError::DatabaseError { .. } => HttpResponse::InternalServerError().finish(),
+                    UserError::InvalidInstance => HttpResponse::InternalServerError().finish(),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserActivityStatsQuery {
+    pub user_id: i32,
+    pub from: DateTime,
+    pub to: DateTime,
+}
+
+#[async_trait]
+impl MessageResponder for UserActivityStatsQuery {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let from = self.from.clone();
+        let to = self.to.clone();
+        let stats = match connection {
+            Connection::Pool(pool) => UserActivityStats::fetch(self.user_id, from, to, pool).await,
+            Connection::Transaction(transaction) => {
+                UserActivityStats::fetch_via_transaction(self.user_id, from, to, transaction).await
+            }
+        };
+        match stats {
             Ok(data) => HttpResponse::Ok()
                 .content_type("application/json; charset=utf-8")
                 .json(data),
             Err(e) => {
-                println!("/user/active-reviewers: {:?}", e);
+                println!("{:?}: {:?}", self, e);
                 match e {
                     UserError::DatabaseError { .. } => HttpResponse::InternalServerError().finish(),
+                    UserError::InvalidInstance => HttpResponse::InternalServerError().finish(),
                 }
             }
         }
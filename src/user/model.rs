@@ -1,10 +1,14 @@
+use std::collections::BTreeMap;
 use std::env;
 
+use serde::{Deserialize, Serialize};
 use sqlx::MySqlPool;
 use thiserror::Error;
 
-use crate::database::Executor;
+use crate::database::{placeholders, Executor};
 use crate::datetime::DateTime;
+use crate::event::RawEventType;
+use crate::instance::Instance;
 
 pub struct User {}
 
@@ -12,6 +16,8 @@ pub struct User {}
 pub enum UserError {
     #[error("Users cannot be fetched because of a database error: {inner:?}.")]
     DatabaseError { inner: sqlx::Error },
+    #[error("Users cannot be fetched because the instance is invalid.")]
+    InvalidInstance,
 }
 
 impl From<sqlx::Error> for UserError {
@@ -20,67 +26,678 @@ impl From<sqlx::Error> for UserError {
     }
 }
 
+/// Describes which users count as active authors or reviewers.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ActivityCriteria {
+    /// Only events of this instance are counted. By default, all instances are taken into account.
+    pub instance: Option<Instance>,
+    /// The number of days before `until` in which the events are counted.
+    pub window_days: i32,
+    /// Users need strictly more events than this to be considered active.
+    pub threshold: i64,
+    /// The event types that are counted. Defaults to the event types of the respective query (see
+    /// [`ActivityCriteria::author_event_types`] and [`ActivityCriteria::reviewer_event_types`]).
+    pub event_types: Option<Vec<RawEventType>>,
+    /// The end of the window. Defaults to the current time (see [`ActivityCriteria::until`]).
+    pub until: Option<DateTime>,
+}
+
+impl Default for ActivityCriteria {
+    fn default() -> Self {
+        ActivityCriteria {
+            instance: None,
+            window_days: 90,
+            threshold: 10,
+            event_types: None,
+            until: None,
+        }
+    }
+}
+
+impl ActivityCriteria {
+    pub fn author_event_types() -> Vec<RawEventType> {
+        vec![RawEventType::CreateEntityRevision]
+    }
+
+    pub fn reviewer_event_types() -> Vec<RawEventType> {
+        vec![RawEventType::CheckoutRevision, RawEventType::RejectRevision]
+    }
+
+    fn until(&self) -> DateTime {
+        self.until.clone().unwrap_or_else(|| {
+            // In the development database there are no recent edits so we use an old timestamp.
+            // In production, we use the current time.
+            match env::var("ENV").as_deref() {
+                Ok("development") => DateTime::ymd(2014, 1, 1),
+                _ => DateTime::now(),
+            }
+        })
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveUser {
+    pub id: i32,
+    pub count: i64,
+}
+
 impl User {
-    pub async fn fetch_active_authors(pool: &MySqlPool) -> Result<Vec<i32>, UserError> {
-        Self::fetch_active_authors_via_transaction(pool).await
+    pub async fn fetch_active_authors(
+        criteria: &ActivityCriteria,
+        pool: &MySqlPool,
+    ) -> Result<Vec<ActiveUser>, UserError> {
+        Self::fetch_active_authors_via_transaction(criteria, pool).await
     }
 
+    /// Returns the users who added more than `criteria.threshold` revisions in the given window,
+    /// ordered by the number of revisions.
     pub async fn fetch_active_authors_via_transaction<'a, E>(
+        criteria: &ActivityCriteria,
         executor: E,
-    ) -> Result<Vec<i32>, UserError>
+    ) -> Result<Vec<ActiveUser>, UserError>
     where
         E: Executor<'a>,
     {
-        let user_ids = sqlx::query!(
+        Self::fetch_authors(criteria, None, executor).await
+    }
+
+    pub async fn fetch_active_reviewers(
+        criteria: &ActivityCriteria,
+        pool: &MySqlPool,
+    ) -> Result<Vec<ActiveUser>, UserError> {
+        Self::fetch_active_reviewers_via_transaction(criteria, pool).await
+    }
+
+    /// Returns the users who reviewed more than `criteria.threshold` revisions of other users in
+    /// the given window, ordered by the number of reviews.
+    pub async fn fetch_active_reviewers_via_transaction<'a, E>(
+        criteria: &ActivityCriteria,
+        executor: E,
+    ) -> Result<Vec<ActiveUser>, UserError>
+    where
+        E: Executor<'a>,
+    {
+        Self::fetch_reviewers(criteria, None, executor).await
+    }
+
+    /// Returns whether the user is one of the active authors with the default criteria (see
+    /// [`User::fetch_active_authors`]).
+    pub async fn is_active_author_via_transaction<'a, E>(
+        user_id: i32,
+        executor: E,
+    ) -> Result<bool, UserError>
+    where
+        E: Executor<'a>,
+    {
+        let users =
+            Self::fetch_authors(&ActivityCriteria::default(), Some(user_id), executor).await?;
+        Ok(!users.is_empty())
+    }
+
+    /// Returns whether the user is one of the active reviewers with the default criteria (see
+    /// [`User::fetch_active_reviewers`]).
+    pub async fn is_active_reviewer_via_transaction<'a, E>(
+        user_id: i32,
+        executor: E,
+    ) -> Result<bool, UserError>
+    where
+        E: Executor<'a>,
+    {
+        let users =
+            Self::fetch_reviewers(&ActivityCriteria::default(), Some(user_id), executor).await?;
+        Ok(!users.is_empty())
+    }
+
+    async fn fetch_authors<'a, E>(
+        criteria: &ActivityCriteria,
+        user_id: Option<i32>,
+        executor: E,
+    ) -> Result<Vec<ActiveUser>, UserError>
+    where
+        E: Executor<'a>,
+    {
+        let event_types = criteria
+            .event_types
+            .clone()
+            .unwrap_or_else(ActivityCriteria::author_event_types);
+        if event_types.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let query = format!(
             r#"
-                SELECT u.id
+                SELECT u.id, COUNT(e.id)
                     FROM user u
                     JOIN event_log e ON u.id = e.actor_id
-                    WHERE e.event_id = 5 AND e.date > DATE_SUB(?, Interval 90 day)
+                    JOIN event t ON t.id = e.event_id
+                    JOIN instance i ON i.id = e.instance_id
+                    WHERE t.name IN ({})
+                        AND e.date > DATE_SUB(?, Interval ? day)
+                        AND (? IS NULL OR i.subdomain = ?)
+                        AND (? IS NULL OR u.id = ?)
                     GROUP BY u.id
-                    HAVING count(e.event_id) > 10
+                    HAVING COUNT(e.id) > ?
+                    ORDER BY COUNT(e.id) DESC, u.id
             "#,
-            Self::now()
-        )
-        .fetch_all(executor)
-        .await?;
-        Ok(user_ids.iter().map(|user| user.id as i32).collect())
-    }
+            placeholders(event_types.len())
+        );
+        let mut query = sqlx::query_as::<_, (i64, i64)>(&query);
+        for event_type in event_types.iter() {
+            query = query.bind(event_type);
+        }
+        let users = query
+            .bind(criteria.until())
+            .bind(criteria.window_days)
+            .bind(criteria.instance.clone())
+            .bind(criteria.instance.clone())
+            .bind(user_id)
+            .bind(user_id)
+            .bind(criteria.threshold)
+            .fetch_all(executor)
+            .await?;
 
-    pub async fn fetch_active_reviewers(pool: &MySqlPool) -> Result<Vec<i32>, UserError> {
-        Self::fetch_active_reviewers_via_transaction(pool).await
+        Ok(users
+            .into_iter()
+            .map(|(id, count)| ActiveUser {
+                id: id as i32,
+                count,
+            })
+            .collect())
     }
 
-    pub async fn fetch_active_reviewers_via_transaction<'a, E>(
+    async fn fetch_reviewers<'a, E>(
+        criteria: &ActivityCriteria,
+        user_id: Option<i32>,
         executor: E,
-    ) -> Result<Vec<i32>, UserError>
+    ) -> Result<Vec<ActiveUser>, UserError>
     where
         E: Executor<'a>,
     {
-        let user_ids = sqlx::query!(
+        let event_types = criteria
+            .event_types
+            .clone()
+            .unwrap_or_else(ActivityCriteria::reviewer_event_types);
+        if event_types.is_empty() {
+            return Ok(vec![]);
+        }
+
+        // A review only counts if it happened after a revision of another user on the same entity.
+        // As in the legacy query, a review is counted once for every such revision.
+        let query = format!(
             r#"
-                SELECT u.id
+                SELECT u.id, COUNT(e1.event_id)
                     FROM event_log e1
-                    JOIN event_log e2 ON e1.uuid_id = e2.uuid_id AND (e1.event_id = 6 OR e1.event_id = 11) AND e2.event_id = 5 AND e1.date >= e2.date AND e1.actor_id != e2.actor_id
+                    JOIN event t1 ON t1.id = e1.event_id
+                    JOIN event_log e2 ON e1.uuid_id = e2.uuid_id AND e1.date >= e2.date AND e1.actor_id != e2.actor_id
+                    JOIN event t2 ON t2.id = e2.event_id
                     JOIN user u ON u.id = e1.actor_id
-                    WHERE e1.date > DATE_SUB(?, Interval 90 day)
+                    JOIN instance i ON i.id = e1.instance_id
+                    WHERE t1.name IN ({})
+                        AND t2.name = ?
+                        AND e1.date > DATE_SUB(?, Interval ? day)
+                        AND (? IS NULL OR i.subdomain = ?)
+                        AND (? IS NULL OR u.id = ?)
                     GROUP BY u.id
-                    HAVING count(e1.event_id) > 10
+                    HAVING COUNT(e1.event_id) > ?
+                    ORDER BY COUNT(e1.event_id) DESC, u.id
             "#,
-            Self::now()
-        )
+            placeholders(event_types.len())
+        );
+        let mut query = sqlx::query_as::<_, (i64, i64)>(&query);
+        for event_type in event_types.iter() {
+            query = query.bind(event_type);
+        }
+        let users = query
+            .bind(RawEventType::CreateEntityRevision)
+            .bind(criteria.until())
+            .bind(criteria.window_days)
+            .bind(criteria.instance.clone())
+            .bind(criteria.instance.clone())
+            .bind(user_id)
+            .bind(user_id)
+            .bind(criteria.threshold)
             .fetch_all(executor)
             .await?;
-        Ok(user_ids.iter().map(|user| user.id as i32).collect())
+
+        Ok(users
+            .into_iter()
+            .map(|(id, count)| ActiveUser {
+                id: id as i32,
+                count,
+            })
+            .collect())
+    }
+}
+
+/// Contributions of a single user, bucketed by month and instance.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserActivityStats {
+    pub user_id: i32,
+    pub from: DateTime,
+    pub to: DateTime,
+    pub series: Vec<UserActivityStatsEntry>,
+}
+
+#[derive(Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserActivityStatsEntry {
+    /// The month formatted as `YYYY-MM` (in local time of the database).
+    pub month: String,
+    pub instance: Instance,
+    pub revisions_created: i64,
+    pub revisions_checked_out: i64,
+    pub revisions_rejected: i64,
+    pub comments_written: i64,
+    pub threads_started: i64,
+    pub entities_created: i64,
+}
+
+impl UserActivityStatsEntry {
+    fn new(month: String, instance: Instance) -> Self {
+        UserActivityStatsEntry {
+            month,
+            instance,
+            revisions_created: 0,
+            revisions_checked_out: 0,
+            revisions_rejected: 0,
+            comments_written: 0,
+            threads_started: 0,
+            entities_created: 0,
+        }
+    }
+
+    fn event_types() -> Vec<RawEventType> {
+        vec![
+            RawEventType::CreateEntityRevision,
+            RawEventType::CheckoutRevision,
+            RawEventType::RejectRevision,
+            RawEventType::CreateComment,
+            RawEventType::CreateThread,
+            RawEventType::CreateEntity,
+        ]
+    }
+
+    fn add(&mut self, event_type: RawEventType, count: i64) {
+        let value = match event_type {
+            RawEventType::CreateEntityRevision => &mut self.revisions_created,
+            RawEventType::CheckoutRevision => &mut self.revisions_checked_out,
+            RawEventType::RejectRevision => &mut self.revisions_rejected,
+            RawEventType::CreateComment => &mut self.comments_written,
+            RawEventType::CreateThread => &mut self.threads_started,
+            RawEventType::CreateEntity => &mut self.entities_created,
+            _ => return,
+        };
+        *value += count;
+    }
+}
+
+impl UserActivityStats {
+    pub async fn fetch(
+        user_id: i32,
+        from: DateTime,
+        to: DateTime,
+        pool: &MySqlPool,
+    ) -> Result<Self, UserError> {
+        Self::fetch_via_transaction(user_id, from, to, pool).await
+    }
+
+    /// Counts the contributions of the user in `[from, to)`. Months and instances without any
+    /// contribution are omitted.
+    pub async fn fetch_via_transaction<'a, E>(
+        user_id: i32,
+        from: DateTime,
+        to: DateTime,
+        executor: E,
+    ) -> Result<Self, UserError>
+    where
+        E: Executor<'a>,
+    {
+        let event_types = UserActivityStatsEntry::event_types();
+        let query = format!(
+            r#"
+                SELECT DATE_FORMAT(e.date, '%Y-%m') AS month, i.subdomain, t.name, COUNT(e.id)
+                    FROM event_log e
+                    JOIN event t ON t.id = e.event_id
+                    JOIN instance i ON i.id = e.instance_id
+                    WHERE e.actor_id = ?
+                        AND e.date >= ?
+                        AND e.date < ?
+                        AND t.name IN ({})
+                    GROUP BY month, i.subdomain, t.name
+            "#,
+            placeholders(event_types.len())
+        );
+        let mut query = sqlx::query_as::<_, (String, String, String, i64)>(&query)
+            .bind(user_id)
+            .bind(&from)
+            .bind(&to);
+        for event_type in event_types.iter() {
+            query = query.bind(event_type);
+        }
+        let rows = query.fetch_all(executor).await?;
+
+        let mut entries: BTreeMap<(String, String), UserActivityStatsEntry> = BTreeMap::new();
+        for (month, subdomain, name, count) in rows {
+            let event_type: RawEventType = match name.parse() {
+                Ok(event_type) => event_type,
+                Err(_) => continue,
+            };
+            let instance = subdomain.parse().map_err(|_| UserError::InvalidInstance)?;
+            entries
+                .entry((month.clone(), subdomain))
+                .or_insert_with(|| UserActivityStatsEntry::new(month, instance))
+                .add(event_type, count);
+        }
+
+        Ok(UserActivityStats {
+            user_id,
+            from,
+            to,
+            series: entries.into_iter().map(|(_, entry)| entry).collect(),
+        })
     }
+}
 
-    fn now() -> DateTime {
-        // In the development database there are no recent edits so we use an old timestamp.
-        // In production, we use the current time.
-        let environment = env::var("ENV").unwrap();
-        match environment.as_str() {
-            "development" => DateTime::ymd(2014, 1, 1),
-            _ => DateTime::now(),
+#[cfg(test)]
+mod tests {
+    use super::{ActiveUser, ActivityCriteria, User, UserActivityStats, UserActivityStatsEntry};
+    use crate::create_database_pool;
+    use crate::datetime::DateTime;
+    use crate::event::RawEventType;
+    use crate::instance::Instance;
+
+    // There are no recent edits in the development database, so we look at an older window.
+    fn criteria() -> ActivityCriteria {
+        ActivityCriteria {
+            until: Some(DateTime::ymd(2014, 1, 1)),
+            ..ActivityCriteria::default()
         }
     }
+
+    #[actix_rt::test]
+    async fn fetch_active_authors() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        // The development database has no events in 2030, so these are the only ones counted.
+        for day in 1..=11 {
+            insert_event(
+                1,
+                1855,
+                RawEventType::CreateEntityRevision,
+                Instance::De,
+                DateTime::ymd(2030, 1, day),
+                &mut transaction,
+            )
+            .await;
+        }
+        insert_event(
+            1,
+            1855,
+            RawEventType::CreateComment,
+            Instance::De,
+            DateTime::ymd(2030, 1, 12),
+            &mut transaction,
+        )
+        .await;
+
+        let authors = User::fetch_active_authors_via_transaction(
+            &ActivityCriteria {
+                until: Some(DateTime::ymd(2030, 2, 1)),
+                ..ActivityCriteria::default()
+            },
+            &mut transaction,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(authors, vec![ActiveUser { id: 1, count: 11 }]);
+    }
+
+    #[actix_rt::test]
+    async fn fetch_active_authors_with_lower_threshold() {
+        let pool = create_database_pool().await.unwrap();
+
+        let authors = User::fetch_active_authors(&criteria(), &pool)
+            .await
+            .unwrap();
+        let more_authors = User::fetch_active_authors(
+            &ActivityCriteria {
+                threshold: 0,
+                ..criteria()
+            },
+            &pool,
+        )
+        .await
+        .unwrap();
+
+        assert!(more_authors.len() >= authors.len());
+        for author in authors.iter() {
+            assert!(more_authors.contains(author));
+        }
+    }
+
+    #[actix_rt::test]
+    async fn fetch_active_authors_of_instance() {
+        let pool = create_database_pool().await.unwrap();
+
+        let authors = User::fetch_active_authors(
+            &ActivityCriteria {
+                threshold: 0,
+                ..criteria()
+            },
+            &pool,
+        )
+        .await
+        .unwrap();
+        let instance_authors = User::fetch_active_authors(
+            &ActivityCriteria {
+                instance: Some(Instance::De),
+                threshold: 0,
+                ..criteria()
+            },
+            &pool,
+        )
+        .await
+        .unwrap();
+
+        for author in instance_authors.iter() {
+            let all = authors.iter().find(|other| other.id == author.id);
+            assert!(all.map_or(false, |all| all.count >= author.count));
+        }
+    }
+
+    #[actix_rt::test]
+    async fn fetch_active_reviewers() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        let author_id = sqlx::query!(r#"SELECT id FROM user WHERE id != 1 LIMIT 1"#)
+            .fetch_one(&mut transaction)
+            .await
+            .unwrap()
+            .id as i32;
+        // A new UUID, so that no older revisions of other users are counted.
+        sqlx::query!(
+            r#"
+                INSERT INTO uuid (trashed, discriminator)
+                    VALUES (0, 'entityRevision')
+            "#
+        )
+        .execute(&mut transaction)
+        .await
+        .unwrap();
+        let uuid_id = sqlx::query!(r#"SELECT LAST_INSERT_ID() as id"#)
+            .fetch_one(&mut transaction)
+            .await
+            .unwrap()
+            .id as i32;
+        // The development database has no events in 2030, so these are the only ones counted.
+        for day in 1..=2 {
+            insert_event(
+                author_id,
+                uuid_id,
+                RawEventType::CreateEntityRevision,
+                Instance::De,
+                DateTime::ymd(2030, 1, day),
+                &mut transaction,
+            )
+            .await;
+        }
+        for day in 3..=8 {
+            insert_event(
+                1,
+                uuid_id,
+                RawEventType::CheckoutRevision,
+                Instance::De,
+                DateTime::ymd(2030, 1, day),
+                &mut transaction,
+            )
+            .await;
+        }
+
+        let reviewers = User::fetch_active_reviewers_via_transaction(
+            &ActivityCriteria {
+                until: Some(DateTime::ymd(2030, 2, 1)),
+                ..ActivityCriteria::default()
+            },
+            &mut transaction,
+        )
+        .await
+        .unwrap();
+
+        // Each of the 6 reviews is counted for both revisions of the author.
+        assert_eq!(reviewers, vec![ActiveUser { id: 1, count: 12 }]);
+    }
+
+    #[actix_rt::test]
+    async fn fetch_active_reviewers_by_event_type() {
+        let pool = create_database_pool().await.unwrap();
+
+        let reviewers = User::fetch_active_reviewers(&criteria(), &pool)
+            .await
+            .unwrap();
+        let checkout_reviewers = User::fetch_active_reviewers(
+            &ActivityCriteria {
+                event_types: Some(vec![RawEventType::CheckoutRevision]),
+                ..criteria()
+            },
+            &pool,
+        )
+        .await
+        .unwrap();
+
+        assert!(reviewers.iter().all(|reviewer| reviewer.count > 10));
+        for reviewer in checkout_reviewers.iter() {
+            let all = reviewers.iter().find(|other| other.id == reviewer.id);
+            assert!(all.map_or(false, |all| all.count >= reviewer.count));
+        }
+    }
+
+    async fn insert_event<'a>(
+        actor_id: i32,
+        uuid_id: i32,
+        event_type: RawEventType,
+        instance: Instance,
+        date: DateTime,
+        transaction: &mut sqlx::Transaction<'a, sqlx::MySql>,
+    ) {
+        sqlx::query!(
+            r#"
+                INSERT INTO event_log (actor_id, event_id, uuid_id, instance_id, date)
+                    SELECT ?, e.id, ?, i.id, ?
+                    FROM event e
+                    JOIN instance i
+                    WHERE e.name = ? AND i.subdomain = ?
+            "#,
+            actor_id,
+            uuid_id,
+            date,
+            event_type,
+            instance
+        )
+        .execute(transaction)
+        .await
+        .unwrap();
+    }
+
+    #[actix_rt::test]
+    async fn fetch_user_activity_stats() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        // The development database has no events in 2030, so these are the only ones counted.
+        let events = vec![
+            (RawEventType::CreateEntityRevision, Instance::De, (1, 10)),
+            (RawEventType::CreateEntityRevision, Instance::De, (1, 20)),
+            (RawEventType::CreateEntity, Instance::De, (1, 20)),
+            (RawEventType::CreateComment, Instance::En, (1, 15)),
+            (RawEventType::CheckoutRevision, Instance::De, (2, 10)),
+            (RawEventType::RejectRevision, Instance::De, (2, 11)),
+            (RawEventType::CreateThread, Instance::De, (2, 12)),
+            // Outside of the window
+            (RawEventType::CreateEntityRevision, Instance::De, (3, 1)),
+        ];
+        for (event_type, instance, (month, day)) in events {
+            insert_event(
+                1,
+                1855,
+                event_type,
+                instance,
+                DateTime::ymd(2030, month, day),
+                &mut transaction,
+            )
+            .await;
+        }
+
+        let stats = UserActivityStats::fetch_via_transaction(
+            1,
+            DateTime::ymd(2030, 1, 1),
+            DateTime::ymd(2030, 3, 1),
+            &mut transaction,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(stats.user_id, 1);
+        assert_eq!(
+            stats.series,
+            vec![
+                UserActivityStatsEntry {
+                    revisions_created: 2,
+                    entities_created: 1,
+                    ..UserActivityStatsEntry::new("2030-01".to_string(), Instance::De)
+                },
+                UserActivityStatsEntry {
+                    comments_written: 1,
+                    ..UserActivityStatsEntry::new("2030-01".to_string(), Instance::En)
+                },
+                UserActivityStatsEntry {
+                    revisions_checked_out: 1,
+                    revisions_rejected: 1,
+                    threads_started: 1,
+                    ..UserActivityStatsEntry::new("2030-02".to_string(), Instance::De)
+                },
+            ]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn fetch_user_activity_stats_of_empty_range() {
+        let pool = create_database_pool().await.unwrap();
+
+        let stats = UserActivityStats::fetch(
+            1,
+            DateTime::ymd(2014, 1, 1),
+            DateTime::ymd(2014, 1, 1),
+            &pool,
+        )
+        .await
+        .unwrap();
+
+        assert!(stats.series.is_empty());
+    }
 }
@@ -7,14 +7,14 @@ use crate::message::MessageResponder;
 
 #[get("/user/active-authors")]
 async fn active_authors(db_pool: web::Data<MySqlPool>) -> impl Responder {
-    let message = ActiveAuthorsQuery {};
+    let message = ActiveAuthorsQuery::default();
     let connection = Connection::Pool(db_pool.get_ref());
     message.handle(connection).await
 }
 
 #[get("/user/active-reviewers")]
 async fn active_reviewers(db_pool: web::Data<MySqlPool>) -> impl Responder {
-    let message = ActiveReviewersQuery {};
+    let message = ActiveReviewersQuery::default();
     let connection = Connection::Pool(db_pool.get_ref());
     message.handle(connection).await
 }
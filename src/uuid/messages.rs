@@ -2,8 +2,17 @@ use actix_web::HttpResponse;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
-use super::model::{Uuid, UuidError, UuidFetcher};
-use crate::database::Connection;
+use super::model::{
+    Attachment, AttachmentAddFileError, AttachmentAddFilePayload, Attachments, BlogPost,
+    BlogPostCreatePayload, BlogPostMutationError, BlogPostUpdatePayload, BlogPosts, Entities,
+    EntitiesFilter, EntityType, Page, PageAddRevisionPayload, PageCheckoutRevisionPayload,
+    PageCreatePayload, PageMutationError, UnrevisedRevisions, User, UserCreatePayload,
+    UserDeleteError, UserDeleteMode, UserDeletePayload, UserMutationError, UserSetLastLoginPayload,
+    UserUpdateDescriptionPayload, Users, UsersFilter, Uuid, UuidError, UuidFetcher,
+};
+use crate::database::{Connection, Executor};
+use crate::datetime::DateTime;
+use crate::instance::Instance;
 use crate::message::MessageResponder;
 use crate::uuid::{SetUuidStateError, SetUuidStatePayload};
 
@@ -11,7 +20,24 @@ use crate::uuid::{SetUuidStateError, SetUuidStatePayload};
 #[serde(tag = "type", content = "payload")]
 pub enum UuidMessage {
     UuidQuery(UuidQuery),
+    UserByUsernameQuery(UserByUsernameQuery),
+    UsersQuery(UsersQuery),
+    ExistsUsernameQuery(ExistsUsernameQuery),
+    UserCreateMutation(UserCreateMutation),
+    UserUpdateDescriptionMutation(UserUpdateDescriptionMutation),
+    UserSetLastLoginMutation(UserSetLastLoginMutation),
+    UserDeleteMutation(UserDeleteMutation),
     UuidSetStateMutation(UuidSetStateMutation),
+    PageCreateMutation(PageCreateMutation),
+    PageAddRevisionMutation(PageAddRevisionMutation),
+    PageCheckoutRevisionMutation(PageCheckoutRevisionMutation),
+    AttachmentsQuery(AttachmentsQuery),
+    AttachmentAddFileMutation(AttachmentAddFileMutation),
+    BlogPostsQuery(BlogPostsQuery),
+    BlogPostCreateMutation(BlogPostCreateMutation),
+    BlogPostUpdateMutation(BlogPostUpdateMutation),
+    UnrevisedRevisionsQuery(UnrevisedRevisionsQuery),
+    EntitiesQuery(EntitiesQuery),
 }
 
 #[async_trait]
@@ -19,7 +45,24 @@ impl MessageResponder for UuidMessage {
     async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
         match self {
             UuidMessage::UuidQuery(message) => message.handle(connection).await,
+            UuidMessage::UserByUsernameQuery(message) => message.handle(connection).await,
+            UuidMessage::UsersQuery(message) => message.handle(connection).await,
+            UuidMessage::ExistsUsernameQuery(message) => message.handle(connection).await,
+            UuidMessage::UserCreateMutation(message) => message.handle(connection).await,
+            UuidMessage::UserUpdateDescriptionMutation(message) => message.handle(connection).await,
+            UuidMessage::UserSetLastLoginMutation(message) => message.handle(connection).await,
+            UuidMessage::UserDeleteMutation(message) => message.handle(connection).await,
             UuidMessage::UuidSetStateMutation(message) => message.handle(connection).await,
+            UuidMessage::PageCreateMutation(message) => message.handle(connection).await,
+            UuidMessage::PageAddRevisionMutation(message) => message.handle(connection).await,
+            UuidMessage::PageCheckoutRevisionMutation(message) => message.handle(connection).await,
+            UuidMessage::AttachmentsQuery(message) => message.handle(connection).await,
+            UuidMessage::AttachmentAddFileMutation(message) => message.handle(connection).await,
+            UuidMessage::BlogPostsQuery(message) => message.handle(connection).await,
+            UuidMessage::BlogPostCreateMutation(message) => message.handle(connection).await,
+            UuidMessage::BlogPostUpdateMutation(message) => message.handle(connection).await,
+            UuidMessage::UnrevisedRevisionsQuery(message) => message.handle(connection).await,
+            UuidMessage::EntitiesQuery(message) => message.handle(connection).await,
         }
     }
 }
@@ -28,39 +71,336 @@ impl MessageResponder for UuidMessage {
 #[serde(rename_all = "camelCase")]
 pub struct UuidQuery {
     pub id: i32,
+    /// Whether the email address of a user should be included. Only privileged callers should set
+    /// this flag.
+    #[serde(default)]
+    pub include_email: bool,
+    /// Whether a user should include whether they are an active author and reviewer.
+    #[serde(default)]
+    pub include_activity: bool,
 }
 
 #[async_trait]
 impl MessageResponder for UuidQuery {
     async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
         let uuid = match connection {
-            Connection::Pool(pool) => Uuid::fetch(self.id, pool).await,
+            Connection::Pool(pool) if !self.include_activity => Uuid::fetch(self.id, pool).await,
+            Connection::Pool(pool) => self.fetch_uuid(pool).await,
+            Connection::Transaction(transaction) => self.fetch_uuid(transaction).await,
+        };
+        match uuid {
+            Ok(uuid) => uuid_response(uuid, self.include_email),
+            Err(e) => {
+                println!("/uuid/{}: {:?}", self.id, e);
+                uuid_error_response(e)
+            }
+        }
+    }
+}
+
+impl UuidQuery {
+    async fn fetch_uuid<'a, E>(&self, executor: E) -> Result<Uuid, UuidError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        let mut uuid = Uuid::fetch_via_transaction(self.id, &mut transaction).await?;
+        if self.include_activity {
+            User::fetch_activity_via_transaction(&mut uuid, &mut transaction).await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(uuid)
+    }
+}
+
+fn uuid_response(mut uuid: Uuid, include_email: bool) -> HttpResponse {
+    if !include_email {
+        User::strip_private_fields(&mut uuid);
+    }
+    HttpResponse::Ok()
+        .content_type("application/json; charset=utf-8")
+        .json(uuid)
+}
+
+fn uuid_error_response(error: UuidError) -> HttpResponse {
+    match error {
+        UuidError::DatabaseError { .. } => HttpResponse::InternalServerError().finish(),
+        UuidError::InvalidInstance => HttpResponse::InternalServerError().finish(),
+        UuidError::UnsupportedDiscriminator { .. } => HttpResponse::NotFound().json(None::<String>),
+        UuidError::UnsupportedEntityType { .. } => HttpResponse::NotFound().json(None::<String>),
+        UuidError::UnsupportedEntityRevisionType { .. } => {
+            HttpResponse::NotFound().json(None::<String>)
+        }
+        UuidError::EntityMissingRequiredParent => HttpResponse::NotFound().json(None::<String>),
+        UuidError::NotFound => HttpResponse::NotFound().json(None::<String>),
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserByUsernameQuery {
+    pub username: String,
+    /// See [`UuidQuery::include_email`].
+    #[serde(default)]
+    pub include_email: bool,
+    /// See [`UuidQuery::include_activity`].
+    #[serde(default)]
+    pub include_activity: bool,
+}
+
+#[async_trait]
+impl MessageResponder for UserByUsernameQuery {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let user = match connection {
+            Connection::Pool(pool) => self.fetch_user(pool).await,
+            Connection::Transaction(transaction) => self.fetch_user(transaction).await,
+        };
+        match user {
+            Ok(user) => uuid_response(user, self.include_email),
+            Err(e) => {
+                println!("{:?}: {:?}", self, e);
+                uuid_error_response(e)
+            }
+        }
+    }
+}
+
+impl UserByUsernameQuery {
+    async fn fetch_user<'a, E>(&self, executor: E) -> Result<Uuid, UuidError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        let mut user =
+            User::fetch_by_username_via_transaction(&self.username, &mut transaction).await?;
+        if self.include_activity {
+            User::fetch_activity_via_transaction(&mut user, &mut transaction).await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(user)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsersQuery {
+    pub username_prefix: Option<String>,
+    pub role: Option<String>,
+    pub registered_after: Option<DateTime>,
+    #[serde(default = "default_users_first")]
+    pub first: i32,
+    pub after: Option<i32>,
+}
+
+fn default_users_first() -> i32 {
+    100
+}
+
+#[async_trait]
+impl MessageResponder for UsersQuery {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let filter = UsersFilter {
+            username_prefix: self.username_prefix.clone(),
+            role: self.role.clone(),
+            registered_after: self.registered_after.clone(),
+        };
+        let first = clamp_first(self.first);
+        let users = match connection {
+            Connection::Pool(pool) => Users::fetch(filter, first, self.after, pool).await,
             Connection::Transaction(transaction) => {
-                Uuid::fetch_via_transaction(self.id, transaction).await
+                Users::fetch_via_transaction(filter, first, self.after, transaction).await
             }
         };
-        match uuid {
-            Ok(uuid) => HttpResponse::Ok()
+        match users {
+            Ok(data) => HttpResponse::Ok()
                 .content_type("application/json; charset=utf-8")
-                .json(uuid),
+                .json(data),
             Err(e) => {
-                println!("/uuid/{}: {:?}", self.id, e);
+                println!("{:?}: {:?}", self, e);
+                HttpResponse::InternalServerError().finish()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExistsUsernameQuery {
+    pub username: String,
+}
+
+#[async_trait]
+impl MessageResponder for ExistsUsernameQuery {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let exists = match connection {
+            Connection::Pool(pool) => User::exists_username(&self.username, pool).await,
+            Connection::Transaction(transaction) => {
+                User::exists_username(&self.username, transaction).await
+            }
+        };
+        match exists {
+            Ok(exists) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(serde_json::json!({ "exists": exists })),
+            Err(e) => {
+                println!("{:?}: {:?}", self, e);
+                HttpResponse::InternalServerError().finish()
+            }
+        }
+    }
+}
+
+fn user_mutation_error_response(error: UserMutationError) -> HttpResponse {
+    match error {
+        UserMutationError::DatabaseError { .. } | UserMutationError::UuidError { .. } => {
+            HttpResponse::InternalServerError().finish()
+        }
+        UserMutationError::InvalidUsername
+        | UserMutationError::UsernameTaken
+        | UserMutationError::EmailTaken
+        | UserMutationError::IdentityProviderIdTaken => {
+            HttpResponse::BadRequest().json(serde_json::json!({ "reason": error.to_string() }))
+        }
+        UserMutationError::NotFound => HttpResponse::NotFound().json(None::<String>),
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserCreateMutation {
+    pub username: String,
+    pub email: String,
+    pub description: Option<String>,
+    pub identity_provider_id: Option<String>,
+}
+
+#[async_trait]
+impl MessageResponder for UserCreateMutation {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let payload = UserCreatePayload {
+            username: self.username.clone(),
+            email: self.email.clone(),
+            description: self.description.clone(),
+            identity_provider_id: self.identity_provider_id.clone(),
+        };
+        let user = match connection {
+            Connection::Pool(pool) => User::create(payload, pool).await,
+            Connection::Transaction(transaction) => User::create(payload, transaction).await,
+        };
+        match user {
+            Ok(data) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(data),
+            Err(e) => {
+                println!("{:?}: {:?}", self, e);
+                user_mutation_error_response(e)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserUpdateDescriptionMutation {
+    pub user_id: i32,
+    pub description: Option<String>,
+}
+
+#[async_trait]
+impl MessageResponder for UserUpdateDescriptionMutation {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let payload = UserUpdateDescriptionPayload {
+            user_id: self.user_id,
+            description: self.description.clone(),
+        };
+        let user = match connection {
+            Connection::Pool(pool) => User::update_description(payload, pool).await,
+            Connection::Transaction(transaction) => {
+                User::update_description(payload, transaction).await
+            }
+        };
+        match user {
+            Ok(user) => uuid_response(user, false),
+            Err(e) => {
+                println!("{:?}: {:?}", self, e);
+                user_mutation_error_response(e)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserSetLastLoginMutation {
+    pub user_id: i32,
+    pub last_login: Option<DateTime>,
+}
+
+#[async_trait]
+impl MessageResponder for UserSetLastLoginMutation {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let payload = UserSetLastLoginPayload {
+            user_id: self.user_id,
+            last_login: self.last_login.clone(),
+        };
+        let user = match connection {
+            Connection::Pool(pool) => User::set_last_login(payload, pool).await,
+            Connection::Transaction(transaction) => {
+                User::set_last_login(payload, transaction).await
+            }
+        };
+        match user {
+            Ok(user) => uuid_response(user, false),
+            Err(e) => {
+                println!("{:?}: {:?}", self, e);
+                user_mutation_error_response(e)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserDeleteMutation {
+    pub user_id: i32,
+    pub mode: UserDeleteMode,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[async_trait]
+impl MessageResponder for UserDeleteMutation {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let payload = UserDeletePayload {
+            user_id: self.user_id,
+            mode: self.mode.clone(),
+            dry_run: self.dry_run,
+        };
+        let report = match connection {
+            Connection::Pool(pool) => User::delete(payload, pool).await,
+            Connection::Transaction(transaction) => User::delete(payload, transaction).await,
+        };
+        match report {
+            Ok(data) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(data),
+            Err(e) => {
+                println!("{:?}: {:?}", self, e);
                 match e {
-                    UuidError::DatabaseError { .. } => HttpResponse::InternalServerError().finish(),
-                    UuidError::InvalidInstance => HttpResponse::InternalServerError().finish(),
-                    UuidError::UnsupportedDiscriminator { .. } => {
-                        HttpResponse::NotFound().json(None::<String>)
-                    }
-                    UuidError::UnsupportedEntityType { .. } => {
-                        HttpResponse::NotFound().json(None::<String>)
-                    }
-                    UuidError::UnsupportedEntityRevisionType { .. } => {
-                        HttpResponse::NotFound().json(None::<String>)
-                    }
-                    UuidError::EntityMissingRequiredParent => {
-                        HttpResponse::NotFound().json(None::<String>)
+                    UserDeleteError::DatabaseError { .. } => {
+                        HttpResponse::InternalServerError().finish()
                     }
-                    UuidError::NotFound => HttpResponse::NotFound().json(None::<String>),
+                    UserDeleteError::HasContributions
+                    | UserDeleteError::PlaceholderNotFound
+                    | UserDeleteError::IsPlaceholder => HttpResponse::BadRequest()
+                        .json(serde_json::json!({ "reason": e.to_string() })),
+                    UserDeleteError::NotFound => HttpResponse::NotFound().json(None::<String>),
                 }
             }
         }
@@ -105,3 +445,467 @@ impl MessageResponder for UuidSetStateMutation {
         }
     }
 }
+
+fn page_mutation_error_response(error: PageMutationError) -> HttpResponse {
+    match error {
+        PageMutationError::DatabaseError { .. }
+        | PageMutationError::EventError { .. }
+        | PageMutationError::UuidError { .. }
+        | PageMutationError::InvalidInstance => HttpResponse::InternalServerError().finish(),
+        PageMutationError::InvalidLicense | PageMutationError::RevisionAlreadyCheckedOut => {
+            HttpResponse::BadRequest().json(serde_json::json!({ "reason": error.to_string() }))
+        }
+        PageMutationError::PageNotFound | PageMutationError::RevisionNotFound => {
+            HttpResponse::NotFound().json(None::<String>)
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageCreateMutation {
+    pub instance: Instance,
+    pub license_id: i32,
+    pub title: String,
+    pub content: String,
+    pub user_id: i32,
+    pub subscribe: bool,
+    pub send_email: bool,
+}
+
+#[async_trait]
+impl MessageResponder for PageCreateMutation {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let payload = PageCreatePayload {
+            instance: self.instance.clone(),
+            license_id: self.license_id,
+            title: self.title.clone(),
+            content: self.content.clone(),
+            user_id: self.user_id,
+            subscribe: self.subscribe,
+            send_email: self.send_email,
+        };
+        let page = match connection {
+            Connection::Pool(pool) => Page::create(payload, pool).await,
+            Connection::Transaction(transaction) => Page::create(payload, transaction).await,
+        };
+        match page {
+            Ok(data) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(data),
+            Err(e) => {
+                println!("{:?}: {:?}", self, e);
+                page_mutation_error_response(e)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageAddRevisionMutation {
+    pub page_id: i32,
+    pub title: String,
+    pub content: String,
+    pub user_id: i32,
+    pub subscribe: bool,
+    pub send_email: bool,
+}
+
+#[async_trait]
+impl MessageResponder for PageAddRevisionMutation {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let payload = PageAddRevisionPayload {
+            page_id: self.page_id,
+            title: self.title.clone(),
+            content: self.content.clone(),
+            user_id: self.user_id,
+            subscribe: self.subscribe,
+            send_email: self.send_email,
+        };
+        let revision = match connection {
+            Connection::Pool(pool) => Page::add_revision(payload, pool).await,
+            Connection::Transaction(transaction) => Page::add_revision(payload, transaction).await,
+        };
+        match revision {
+            Ok(data) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(data),
+            Err(e) => {
+                println!("{:?}: {:?}", self, e);
+                page_mutation_error_response(e)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageCheckoutRevisionMutation {
+    pub revision_id: i32,
+    #[serde(default)]
+    pub reason: String,
+    pub user_id: i32,
+}
+
+#[async_trait]
+impl MessageResponder for PageCheckoutRevisionMutation {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let payload = PageCheckoutRevisionPayload {
+            revision_id: self.revision_id,
+            reason: self.reason.clone(),
+            user_id: self.user_id,
+        };
+        let page = match connection {
+            Connection::Pool(pool) => Page::checkout_revision(payload, pool).await,
+            Connection::Transaction(transaction) => {
+                Page::checkout_revision(payload, transaction).await
+            }
+        };
+        match page {
+            Ok(data) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(data),
+            Err(e) => {
+                println!("{:?}: {:?}", self, e);
+                page_mutation_error_response(e)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentsQuery {
+    pub instance: Instance,
+    #[serde(default = "default_attachments_first")]
+    pub first: i32,
+    pub after: Option<i32>,
+}
+
+fn default_attachments_first() -> i32 {
+    100
+}
+
+/// Maximum number of items a paginated query returns at once.
+const MAX_FIRST: i32 = 1000;
+
+/// Restricts the requested page size to `1..=MAX_FIRST`.
+fn clamp_first(first: i32) -> i32 {
+    first.max(1).min(MAX_FIRST)
+}
+
+#[async_trait]
+impl MessageResponder for AttachmentsQuery {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let instance = self.instance.clone();
+        let first = clamp_first(self.first);
+        let attachments = match connection {
+            Connection::Pool(pool) => Attachments::fetch(instance, first, self.after, pool).await,
+            Connection::Transaction(transaction) => {
+                Attachments::fetch_via_transaction(instance, first, self.after, transaction).await
+            }
+        };
+        match attachments {
+            Ok(data) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(data),
+            Err(e) => {
+                println!("{:?}: {:?}", self, e);
+                HttpResponse::InternalServerError().finish()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentAddFileMutation {
+    pub attachment_id: i32,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub file_type: String,
+    pub size: Option<i32>,
+    pub location: String,
+}
+
+#[async_trait]
+impl MessageResponder for AttachmentAddFileMutation {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let payload = AttachmentAddFilePayload {
+            attachment_id: self.attachment_id,
+            name: self.name.clone(),
+            file_type: self.file_type.clone(),
+            size: self.size,
+            location: self.location.clone(),
+        };
+        let attachment = match connection {
+            Connection::Pool(pool) => Attachment::add_file(payload, pool).await,
+            Connection::Transaction(transaction) => {
+                Attachment::add_file(payload, transaction).await
+            }
+        };
+        match attachment {
+            Ok(data) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(data),
+            Err(e) => {
+                println!("{:?}: {:?}", self, e);
+                match e {
+                    AttachmentAddFileError::DatabaseError { .. }
+                    | AttachmentAddFileError::UuidError { .. } => {
+                        HttpResponse::InternalServerError().finish()
+                    }
+                    AttachmentAddFileError::AttachmentNotFound => {
+                        HttpResponse::NotFound().json(None::<String>)
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlogPostsQuery {
+    pub instance: Instance,
+    #[serde(default = "default_blog_posts_first")]
+    pub first: i32,
+    pub after: Option<i32>,
+    #[serde(default)]
+    pub include_unpublished: bool,
+}
+
+fn default_blog_posts_first() -> i32 {
+    10
+}
+
+#[async_trait]
+impl MessageResponder for BlogPostsQuery {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let instance = self.instance.clone();
+        let first = clamp_first(self.first);
+        let blog_posts = match connection {
+            Connection::Pool(pool) => {
+                BlogPosts::fetch(instance, first, self.after, self.include_unpublished, pool).await
+            }
+            Connection::Transaction(transaction) => {
+                BlogPosts::fetch_via_transaction(
+                    instance,
+                    first,
+                    self.after,
+                    self.include_unpublished,
+                    transaction,
+                )
+                .await
+            }
+        };
+        match blog_posts {
+            Ok(data) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(data),
+            Err(e) => {
+                println!("{:?}: {:?}", self, e);
+                HttpResponse::InternalServerError().finish()
+            }
+        }
+    }
+}
+
+fn blog_post_mutation_error_response(error: BlogPostMutationError) -> HttpResponse {
+    match error {
+        BlogPostMutationError::DatabaseError { .. }
+        | BlogPostMutationError::EventError { .. }
+        | BlogPostMutationError::UuidError { .. } => HttpResponse::InternalServerError().finish(),
+        BlogPostMutationError::InvalidTaxonomyTerm => {
+            HttpResponse::BadRequest().json(serde_json::json!({ "reason": error.to_string() }))
+        }
+        BlogPostMutationError::NotFound => HttpResponse::NotFound().json(None::<String>),
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlogPostCreateMutation {
+    pub instance: Instance,
+    pub user_id: i32,
+    pub taxonomy_term_id: i32,
+    pub title: String,
+    pub content: String,
+    pub publish_date: Option<DateTime>,
+    pub subscribe: bool,
+    pub send_email: bool,
+}
+
+#[async_trait]
+impl MessageResponder for BlogPostCreateMutation {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let payload = BlogPostCreatePayload {
+            instance: self.instance.clone(),
+            user_id: self.user_id,
+            taxonomy_term_id: self.taxonomy_term_id,
+            title: self.title.clone(),
+            content: self.content.clone(),
+            publish_date: self.publish_date.clone(),
+            subscribe: self.subscribe,
+            send_email: self.send_email,
+        };
+        let blog_post = match connection {
+            Connection::Pool(pool) => BlogPost::create(payload, pool).await,
+            Connection::Transaction(transaction) => BlogPost::create(payload, transaction).await,
+        };
+        match blog_post {
+            Ok(data) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(data),
+            Err(e) => {
+                println!("{:?}: {:?}", self, e);
+                blog_post_mutation_error_response(e)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlogPostUpdateMutation {
+    pub id: i32,
+    pub title: String,
+    pub content: String,
+    pub publish_date: Option<DateTime>,
+}
+
+#[async_trait]
+impl MessageResponder for BlogPostUpdateMutation {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let payload = BlogPostUpdatePayload {
+            id: self.id,
+            title: self.title.clone(),
+            content: self.content.clone(),
+            publish_date: self.publish_date.clone(),
+        };
+        let blog_post = match connection {
+            Connection::Pool(pool) => BlogPost::update(payload, pool).await,
+            Connection::Transaction(transaction) => BlogPost::update(payload, transaction).await,
+        };
+        match blog_post {
+            Ok(data) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(data),
+            Err(e) => {
+                println!("{:?}: {:?}", self, e);
+                blog_post_mutation_error_response(e)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnrevisedRevisionsQuery {
+    pub instance: Instance,
+    pub subject: Option<String>,
+    #[serde(default = "default_unrevised_revisions_first")]
+    pub first: i32,
+    pub after: Option<i32>,
+}
+
+fn default_unrevised_revisions_first() -> i32 {
+    20
+}
+
+#[async_trait]
+impl MessageResponder for UnrevisedRevisionsQuery {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let instance = self.instance.clone();
+        let subject = self.subject.clone();
+        let first = clamp_first(self.first);
+        let unrevised_revisions = match connection {
+            Connection::Pool(pool) => {
+                UnrevisedRevisions::fetch(instance, subject, first, self.after, pool).await
+            }
+            Connection::Transaction(transaction) => {
+                UnrevisedRevisions::fetch_via_transaction(
+                    instance,
+                    subject,
+                    first,
+                    self.after,
+                    transaction,
+                )
+                .await
+            }
+        };
+        match unrevised_revisions {
+            Ok(data) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(data),
+            Err(e) => {
+                println!("{:?}: {:?}", self, e);
+                HttpResponse::InternalServerError().finish()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntitiesQuery {
+    pub entity_type: Option<EntityType>,
+    pub instance: Option<Instance>,
+    pub taxonomy_term_id: Option<i32>,
+    pub license_id: Option<i32>,
+    pub trashed: Option<bool>,
+    pub has_current_revision: Option<bool>,
+    pub created_after: Option<DateTime>,
+    pub created_before: Option<DateTime>,
+    #[serde(default = "default_entities_first")]
+    pub first: i32,
+    pub after: Option<i32>,
+    #[serde(default)]
+    pub include_entities: bool,
+}
+
+fn default_entities_first() -> i32 {
+    100
+}
+
+#[async_trait]
+impl MessageResponder for EntitiesQuery {
+    async fn handle(&self, connection: Connection<'_, '_>) -> HttpResponse {
+        let filter = EntitiesFilter {
+            entity_type: self.entity_type.clone(),
+            instance: self.instance.clone(),
+            taxonomy_term_id: self.taxonomy_term_id,
+            license_id: self.license_id,
+            trashed: self.trashed,
+            has_current_revision: self.has_current_revision,
+            created_after: self.created_after.clone(),
+            created_before: self.created_before.clone(),
+        };
+        let first = clamp_first(self.first);
+        let entities = match connection {
+            Connection::Pool(pool) => {
+                Entities::fetch(filter, first, self.after, self.include_entities, pool).await
+            }
+            Connection::Transaction(transaction) => {
+                Entities::fetch_via_transaction(
+                    filter,
+                    first,
+                    self.after,
+                    self.include_entities,
+                    transaction,
+                )
+                .await
+            }
+        };
+        match entities {
+            Ok(data) => HttpResponse::Ok()
+                .content_type("application/json; charset=utf-8")
+                .json(data),
+            Err(e) => {
+                println!("{:?}: {:?}", self, e);
+                HttpResponse::InternalServerError().finish()
+            }
+        }
+    }
+}
@@ -1,11 +1,57 @@
+use std::collections::HashMap;
+
 use async_trait::async_trait;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
 use sqlx::MySqlPool;
+use thiserror::Error;
 
 use super::{ConcreteUuid, Uuid, UuidError, UuidFetcher};
-use crate::database::Executor;
+use crate::database::{fetch_all_by_ids, Executor};
+use crate::datetime::DateTime;
 use crate::format_alias;
+use crate::instance::Instance;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attachment {
+    #[serde(rename(serialize = "__typename"))]
+    pub __typename: String,
+    pub instance: Instance,
+    /// The legacy schema doesn't store when an attachment was uploaded, so this is the date of the
+    /// first event logged for the attachment (if any).
+    pub date: Option<DateTime>,
+    pub files: Vec<AttachmentFile>,
+}
 
-pub struct Attachment {}
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentFile {
+    pub id: i32,
+    pub name: String,
+    #[serde(rename(serialize = "type"))]
+    pub file_type: String,
+    pub size: Option<i32>,
+    pub location: String,
+}
+
+macro_rules! fetch_one_attachment {
+    ($id: expr, $executor: expr) => {
+        sqlx::query!(
+            r#"
+                SELECT u.trashed, i.subdomain, MIN(l.date) as date
+                    FROM attachment_container c
+                    JOIN uuid u ON u.id = c.id
+                    JOIN instance i ON i.id = c.instance_id
+                    LEFT JOIN event_log l ON l.uuid_id = c.id
+                    WHERE c.id = ?
+                    GROUP BY u.trashed, i.subdomain
+            "#,
+            $id
+        )
+        .fetch_one($executor)
+    };
+}
 
 #[async_trait]
 impl UuidFetcher for Attachment {
@@ -17,37 +63,401 @@ impl UuidFetcher for Attachment {
     where
         E: Executor<'a>,
     {
-        sqlx::query!(
+        let mut transaction = executor.begin().await?;
+
+        let attachment = fetch_one_attachment!(id, &mut transaction)
+            .await
+            .map_err(|error| match error {
+                sqlx::Error::RowNotFound => UuidError::NotFound,
+                error => error.into(),
+            })?;
+        let files = Self::fetch_files_via_transaction(&[id], &mut transaction)
+            .await?
+            .remove(&id)
+            .unwrap_or_default();
+
+        transaction.commit().await?;
+
+        Self::to_uuid(
+            id,
+            attachment.trashed != 0,
+            &attachment.subdomain,
+            attachment.date,
+            files,
+        )
+    }
+}
+
+impl Attachment {
+    pub fn get_context() -> Option<String> {
+        Some("attachment".to_string())
+    }
+
+    /// Returns the alias of the attachment, which is determined by its first file. Attachments
+    /// without any file have no alias.
+    fn get_alias(id: i32, instance: &Instance, files: &[AttachmentFile]) -> Option<String> {
+        files.first().map(|file| {
+            format_alias(
+                Some(instance),
+                Self::get_context().as_deref(),
+                id,
+                Some(&file.name),
+            )
+        })
+    }
+
+    /// Fetches the aliases of the given attachments without loading the attachments themselves.
+    /// Attachments that do not exist or have no file are left out.
+    pub async fn fetch_aliases_via_transaction<'a, E>(
+        ids: &[i32],
+        executor: E,
+    ) -> Result<HashMap<i32, String>, UuidError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        let containers: Vec<(i64, String)> = fetch_all_by_ids(
             r#"
-                SELECT u.trashed, f.name
-                    FROM attachment_file f
-                    JOIN attachment_container c ON c.id = f.attachment_id
-                    JOIN uuid u ON u.id = c.id
-                    WHERE c.id = ?
+                SELECT c.id, i.subdomain
+                    FROM attachment_container c
+                    JOIN instance i ON i.id = c.instance_id
+                    WHERE c.id IN ({})
             "#,
-            id
+            ids,
+            &mut transaction,
         )
-        .fetch_one(executor)
-        .await
-        .map_err(|error| match error {
-            sqlx::Error::RowNotFound => UuidError::NotFound,
-            error => error.into(),
-        })
-        .map(|attachment| Uuid {
+        .await?;
+        let mut files = Self::fetch_files_via_transaction(ids, &mut transaction).await?;
+
+        transaction.commit().await?;
+
+        let mut aliases = HashMap::new();
+        for (id, subdomain) in containers {
+            let id = id as i32;
+            let instance: Instance = subdomain.parse().map_err(|_| UuidError::InvalidInstance)?;
+            let files = files.remove(&id).unwrap_or_default();
+            if let Some(alias) = Self::get_alias(id, &instance, &files) {
+                aliases.insert(id, alias);
+            }
+        }
+        Ok(aliases)
+    }
+
+    /// Fetches the files of the given attachments, ordered by id.
+    async fn fetch_files_via_transaction<'a, E>(
+        ids: &[i32],
+        executor: E,
+    ) -> Result<HashMap<i32, Vec<AttachmentFile>>, sqlx::Error>
+    where
+        E: Executor<'a>,
+    {
+        let rows: Vec<(i64, i64, String, String, Option<i32>, String)> = fetch_all_by_ids(
+            "SELECT id, attachment_id, name, type, size, location FROM attachment_file WHERE attachment_id IN ({}) ORDER BY id",
+            ids,
+            executor,
+        )
+        .await?;
+        let mut files: HashMap<i32, Vec<AttachmentFile>> = HashMap::new();
+        for (id, attachment_id, name, file_type, size, location) in rows {
+            files
+                .entry(attachment_id as i32)
+                .or_insert_with(Vec::new)
+                .push(AttachmentFile {
+                    id: id as i32,
+                    name,
+                    file_type,
+                    size,
+                    location,
+                });
+        }
+        Ok(files)
+    }
+
+    fn to_uuid(
+        id: i32,
+        trashed: bool,
+        subdomain: &str,
+        date: Option<chrono::DateTime<Utc>>,
+        files: Vec<AttachmentFile>,
+    ) -> Result<Uuid, UuidError> {
+        let instance: Instance = subdomain.parse().map_err(|_| UuidError::InvalidInstance)?;
+        // An attachment always has at least one file.
+        let alias = Self::get_alias(id, &instance, &files).ok_or(UuidError::NotFound)?;
+
+        Ok(Uuid {
             id,
-            trashed: attachment.trashed != 0,
-            alias: format_alias(
-                Self::get_context().as_deref(),
-                id,
-                Some(attachment.name.as_str()),
-            ),
-            concrete_uuid: ConcreteUuid::Attachment,
+            trashed,
+            alias,
+            concrete_uuid: ConcreteUuid::Attachment(Attachment {
+                __typename: "Attachment".to_string(),
+                instance,
+                date: date.map(|date| date.into()),
+                files,
+            }),
         })
     }
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attachments {
+    pub attachments: Vec<Uuid>,
+}
+
+impl Attachments {
+    pub async fn fetch(
+        instance: Instance,
+        first: i32,
+        after: Option<i32>,
+        pool: &MySqlPool,
+    ) -> Result<Self, UuidError> {
+        Self::fetch_via_transaction(instance, first, after, pool).await
+    }
+
+    /// Fetches the first `first` attachments of the instance whose id is greater than `after`,
+    /// ordered by id.
+    pub async fn fetch_via_transaction<'a, E>(
+        instance: Instance,
+        first: i32,
+        after: Option<i32>,
+        executor: E,
+    ) -> Result<Self, UuidError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        let containers = sqlx::query!(
+            r#"
+                SELECT c.id, u.trashed, i.subdomain, MIN(l.date) as date
+                    FROM attachment_container c
+                    JOIN uuid u ON u.id = c.id
+                    JOIN instance i ON i.id = c.instance_id
+                    LEFT JOIN event_log l ON l.uuid_id = c.id
+                    WHERE i.subdomain = ? AND c.id > ?
+                    GROUP BY c.id, u.trashed, i.subdomain
+                    ORDER BY c.id
+                    LIMIT ?
+            "#,
+            instance,
+            after.unwrap_or(0),
+            first
+        )
+        .fetch_all(&mut transaction)
+        .await?;
+
+        let ids: Vec<i32> = containers
+            .iter()
+            .map(|container| container.id as i32)
+            .collect();
+        let mut files = Attachment::fetch_files_via_transaction(&ids, &mut transaction).await?;
+
+        transaction.commit().await?;
+
+        let attachments = containers
+            .into_iter()
+            .filter_map(|container| {
+                let id = container.id as i32;
+                let files = files.remove(&id).unwrap_or_default();
+                // Containers without any file are skipped like in `Attachment::fetch`.
+                match Attachment::to_uuid(
+                    id,
+                    container.trashed != 0,
+                    &container.subdomain,
+                    container.date,
+                    files,
+                ) {
+                    Err(UuidError::NotFound) => None,
+                    result => Some(result),
+                }
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Attachments { attachments })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentAddFilePayload {
+    pub attachment_id: i32,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub file_type: String,
+    pub size: Option<i32>,
+    pub location: String,
+}
+
+#[derive(Error, Debug)]
+pub enum AttachmentAddFileError {
+    #[error("File cannot be added because of a database error: {inner:?}.")]
+    DatabaseError { inner: sqlx::Error },
+    #[error("File cannot be added because of an uuid error: {inner:?}.")]
+    UuidError { inner: UuidError },
+    #[error("File cannot be added because the attachment does not exist.")]
+    AttachmentNotFound,
+}
+
+impl From<sqlx::Error> for AttachmentAddFileError {
+    fn from(inner: sqlx::Error) -> Self {
+        AttachmentAddFileError::DatabaseError { inner }
+    }
+}
+
+impl From<UuidError> for AttachmentAddFileError {
+    fn from(error: UuidError) -> Self {
+        match error {
+            UuidError::DatabaseError { inner } => inner.into(),
+            inner => AttachmentAddFileError::UuidError { inner },
+        }
+    }
+}
+
 impl Attachment {
-    pub fn get_context() -> Option<String> {
-        Some("attachment".to_string())
+    /// Registers a new file on an existing attachment and returns the updated attachment.
+    pub async fn add_file<'a, E>(
+        payload: AttachmentAddFilePayload,
+        executor: E,
+    ) -> Result<Uuid, AttachmentAddFileError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        let container = sqlx::query!(
+            r#"SELECT id FROM attachment_container WHERE id = ?"#,
+            payload.attachment_id
+        )
+        .fetch_optional(&mut transaction)
+        .await?;
+        if container.is_none() {
+            return Err(AttachmentAddFileError::AttachmentNotFound);
+        }
+
+        sqlx::query!(
+            r#"
+                INSERT INTO attachment_file (name, type, size, location, attachment_id)
+                    VALUES (?, ?, ?, ?, ?)
+            "#,
+            payload.name,
+            payload.file_type,
+            payload.size,
+            payload.location,
+            payload.attachment_id
+        )
+        .execute(&mut transaction)
+        .await?;
+
+        let attachment =
+            Attachment::fetch_via_transaction(payload.attachment_id, &mut transaction).await?;
+
+        transaction.commit().await?;
+
+        Ok(attachment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AttachmentAddFileError, AttachmentAddFilePayload, Attachments};
+    use crate::create_database_pool;
+    use crate::instance::Instance;
+    use crate::uuid::{Attachment, ConcreteUuid, UuidFetcher};
+
+    #[actix_rt::test]
+    async fn attachments() {
+        let pool = create_database_pool().await.unwrap();
+
+        let attachments = Attachments::fetch(Instance::De, 2, None, &pool)
+            .await
+            .unwrap()
+            .attachments;
+
+        assert!(attachments.len() <= 2);
+        for attachment in attachments.iter() {
+            if let ConcreteUuid::Attachment(attachment) = &attachment.concrete_uuid {
+                assert_eq!(attachment.instance, Instance::De);
+                assert!(!attachment.files.is_empty());
+            } else {
+                panic!("UUID is not an attachment")
+            }
+        }
+
+        if let Some(last) = attachments.last() {
+            let next = Attachments::fetch(Instance::De, 2, Some(last.id), &pool)
+                .await
+                .unwrap()
+                .attachments;
+            assert!(next.iter().all(|attachment| attachment.id > last.id));
+        }
+    }
+
+    #[actix_rt::test]
+    async fn add_file() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        let attachments =
+            Attachments::fetch_via_transaction(Instance::De, 1, None, &mut transaction)
+                .await
+                .unwrap()
+                .attachments;
+        let attachment_id = match attachments.first() {
+            Some(attachment) => attachment.id,
+            None => return,
+        };
+        let files_before = match Attachment::fetch_via_transaction(attachment_id, &mut transaction)
+            .await
+            .unwrap()
+            .concrete_uuid
+        {
+            ConcreteUuid::Attachment(attachment) => attachment.files.len(),
+            _ => panic!("UUID is not an attachment"),
+        };
+
+        let uuid = Attachment::add_file(
+            AttachmentAddFilePayload {
+                attachment_id,
+                name: "image.png".to_string(),
+                file_type: "image/png".to_string(),
+                size: Some(1024),
+                location: "https://assets.serlo.org/image.png".to_string(),
+            },
+            &mut transaction,
+        )
+        .await
+        .unwrap();
+
+        if let ConcreteUuid::Attachment(attachment) = uuid.concrete_uuid {
+            assert_eq!(attachment.files.len(), files_before + 1);
+            let file = attachment.files.last().unwrap();
+            assert_eq!(file.name, "image.png");
+            assert_eq!(file.file_type, "image/png");
+        } else {
+            panic!("UUID is not an attachment")
+        }
+    }
+
+    #[actix_rt::test]
+    async fn add_file_to_nonexistent_attachment() {
+        let pool = create_database_pool().await.unwrap();
+
+        let result = Attachment::add_file(
+            AttachmentAddFilePayload {
+                attachment_id: 1,
+                name: "image.png".to_string(),
+                file_type: "image/png".to_string(),
+                size: None,
+                location: "https://assets.serlo.org/image.png".to_string(),
+            },
+            &pool,
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(AttachmentAddFileError::AttachmentNotFound)
+        ));
     }
 }
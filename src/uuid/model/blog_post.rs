@@ -1,11 +1,60 @@
+use std::collections::HashMap;
+
 use async_trait::async_trait;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
 use sqlx::MySqlPool;
+use thiserror::Error;
 
 use super::{ConcreteUuid, Uuid, UuidError, UuidFetcher};
-use crate::database::Executor;
+use crate::database::{fetch_all_by_ids, Executor};
+use crate::datetime::DateTime;
+use crate::event::{CreateTaxonomyLinkEventPayload, EventError};
 use crate::format_alias;
+use crate::instance::Instance;
+use crate::subscription::{Subscription, SubscriptionChangeError};
 
-pub struct BlogPost {}
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlogPost {
+    #[serde(rename(serialize = "__typename"))]
+    pub __typename: String,
+    pub instance: Instance,
+    pub title: String,
+    pub content: String,
+    pub author_id: i32,
+    pub date: DateTime,
+    /// `None` if the blog post has not been published (yet).
+    pub publish_date: Option<DateTime>,
+    pub taxonomy_term_id: i32,
+}
+
+macro_rules! to_blog_post {
+    ($id: expr, $blog: expr) => {{
+        let blog = $blog;
+        let instance: Instance = blog
+            .subdomain
+            .parse()
+            .map_err(|_| UuidError::InvalidInstance)?;
+        Ok::<Uuid, UuidError>(Uuid {
+            id: $id,
+            trashed: blog.trashed != 0,
+            alias: BlogPost::get_alias($id, &instance, &blog.title),
+            concrete_uuid: ConcreteUuid::BlogPost(BlogPost {
+                __typename: "BlogPost".to_string(),
+                instance,
+                title: blog.title,
+                content: blog.content,
+                author_id: blog.author_id as i32,
+                date: blog.date.into(),
+                publish_date: blog
+                    .publish
+                    .map(|publish: chrono::DateTime<Utc>| publish.into()),
+                taxonomy_term_id: blog.category_id as i32,
+            }),
+        })
+    }};
+}
 
 #[async_trait]
 impl UuidFetcher for BlogPost {
@@ -17,11 +66,12 @@ impl UuidFetcher for BlogPost {
     where
         E: Executor<'a>,
     {
-        sqlx::query!(
+        let blog = sqlx::query!(
             r#"
-                SELECT u.trashed, b.title
+                SELECT u.trashed, i.subdomain, b.title, b.content, b.author_id, b.date, b.publish, b.category_id
                     FROM blog_post b
                     JOIN uuid u ON u.id = b.id
+                    JOIN instance i ON i.id = b.instance_id
                     WHERE b.id = ?
             "#,
             id
@@ -31,17 +81,9 @@ impl UuidFetcher for BlogPost {
         .map_err(|error| match error {
             sqlx::Error::RowNotFound => UuidError::NotFound,
             error => error.into(),
-        })
-        .map(|blog| Uuid {
-            id,
-            trashed: blog.trashed != 0,
-            alias: format_alias(
-                Self::get_context().as_deref(),
-                id,
-                Some(blog.title.as_str()),
-            ),
-            concrete_uuid: ConcreteUuid::BlogPost,
-        })
+        })?;
+
+        to_blog_post!(id, blog)
     }
 }
 
@@ -49,4 +91,447 @@ impl BlogPost {
     pub fn get_context() -> Option<String> {
         Some("blog".to_string())
     }
+
+    fn get_alias(id: i32, instance: &Instance, title: &str) -> String {
+        format_alias(
+            Some(instance),
+            Self::get_context().as_deref(),
+            id,
+            Some(title),
+        )
+    }
+
+    /// Fetches the aliases of the given blog posts without loading the blog posts themselves.
+    /// Blog posts that do not exist are left out.
+    pub async fn fetch_aliases_via_transaction<'a, E>(
+        ids: &[i32],
+        executor: E,
+    ) -> Result<HashMap<i32, String>, UuidError>
+    where
+        E: Executor<'a>,
+    {
+        let blog_posts: Vec<(i64, String, String)> = fetch_all_by_ids(
+            r#"
+                SELECT b.id, i.subdomain, b.title
+                    FROM blog_post b
+                    JOIN instance i ON i.id = b.instance_id
+                    WHERE b.id IN ({})
+            "#,
+            ids,
+            executor,
+        )
+        .await?;
+        blog_posts
+            .into_iter()
+            .map(|(id, subdomain, title)| {
+                let id = id as i32;
+                let instance: Instance =
+                    subdomain.parse().map_err(|_| UuidError::InvalidInstance)?;
+                Ok((id, Self::get_alias(id, &instance, &title)))
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlogPosts {
+    pub blog_posts: Vec<Uuid>,
+}
+
+impl BlogPosts {
+    pub async fn fetch(
+        instance: Instance,
+        first: i32,
+        after: Option<i32>,
+        include_unpublished: bool,
+        pool: &MySqlPool,
+    ) -> Result<Self, UuidError> {
+        Self::fetch_via_transaction(instance, first, after, include_unpublished, pool).await
+    }
+
+    /// Fetches the newest `first` non-trashed blog posts of the instance whose id is smaller than
+    /// `after`.
+    pub async fn fetch_via_transaction<'a, E>(
+        instance: Instance,
+        first: i32,
+        after: Option<i32>,
+        include_unpublished: bool,
+        executor: E,
+    ) -> Result<Self, UuidError>
+    where
+        E: Executor<'a>,
+    {
+        let blogs = sqlx::query!(
+            r#"
+                SELECT b.id, u.trashed, i.subdomain, b.title, b.content, b.author_id, b.date, b.publish, b.category_id
+                    FROM blog_post b
+                    JOIN uuid u ON u.id = b.id
+                    JOIN instance i ON i.id = b.instance_id
+                    WHERE i.subdomain = ?
+                        AND u.trashed = 0
+                        AND (? IS NULL OR b.id < ?)
+                        AND (? OR (b.publish IS NOT NULL AND b.publish <= ?))
+                    ORDER BY b.id DESC
+                    LIMIT ?
+            "#,
+            instance,
+            after,
+            after,
+            include_unpublished,
+            DateTime::now(),
+            first
+        )
+        .fetch_all(executor)
+        .await?;
+
+        let blog_posts = blogs
+            .into_iter()
+            .map(|blog| to_blog_post!(blog.id as i32, blog))
+            .collect::<Result<_, _>>()?;
+
+        Ok(BlogPosts { blog_posts })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlogPostCreatePayload {
+    pub instance: Instance,
+    pub user_id: i32,
+    pub taxonomy_term_id: i32,
+    pub title: String,
+    pub content: String,
+    pub publish_date: Option<DateTime>,
+    pub subscribe: bool,
+    pub send_email: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlogPostUpdatePayload {
+    pub id: i32,
+    pub title: String,
+    pub content: String,
+    pub publish_date: Option<DateTime>,
+}
+
+#[derive(Error, Debug)]
+pub enum BlogPostMutationError {
+    #[error("BlogPost cannot be changed because of a database error: {inner:?}.")]
+    DatabaseError { inner: sqlx::Error },
+    #[error("BlogPost cannot be changed because of an event error: {inner:?}.")]
+    EventError { inner: EventError },
+    #[error("BlogPost cannot be changed because of an uuid error: {inner:?}.")]
+    UuidError { inner: UuidError },
+    #[error("BlogPost cannot be changed because the taxonomy term is not a blog of its instance.")]
+    InvalidTaxonomyTerm,
+    #[error("BlogPost cannot be changed because it does not exist.")]
+    NotFound,
+}
+
+impl From<sqlx::Error> for BlogPostMutationError {
+    fn from(inner: sqlx::Error) -> Self {
+        BlogPostMutationError::DatabaseError { inner }
+    }
+}
+
+impl From<EventError> for BlogPostMutationError {
+    fn from(error: EventError) -> Self {
+        match error {
+            EventError::DatabaseError { inner } => inner.into(),
+            inner => BlogPostMutationError::EventError { inner },
+        }
+    }
+}
+
+impl From<SubscriptionChangeError> for BlogPostMutationError {
+    fn from(error: SubscriptionChangeError) -> Self {
+        match error {
+            SubscriptionChangeError::DatabaseError { inner } => inner.into(),
+        }
+    }
+}
+
+impl From<UuidError> for BlogPostMutationError {
+    fn from(error: UuidError) -> Self {
+        match error {
+            UuidError::DatabaseError { inner } => inner.into(),
+            UuidError::NotFound => BlogPostMutationError::NotFound,
+            inner => BlogPostMutationError::UuidError { inner },
+        }
+    }
+}
+
+impl BlogPost {
+    /// Creates a new blog post in the blog (taxonomy term) of the payload. The legacy event log
+    /// has no event types for blog posts, so the creation is recorded as linking the post to its
+    /// blog.
+    pub async fn create<'a, E>(
+        payload: BlogPostCreatePayload,
+        executor: E,
+    ) -> Result<Uuid, BlogPostMutationError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        let blog = sqlx::query!(
+            r#"
+                SELECT tt.id
+                    FROM term_taxonomy tt
+                    JOIN taxonomy t ON t.id = tt.taxonomy_id
+                    JOIN type ty ON ty.id = t.type_id
+                    JOIN instance i ON i.id = t.instance_id
+                    WHERE tt.id = ? AND ty.name = 'blog' AND i.subdomain = ?
+            "#,
+            payload.taxonomy_term_id,
+            payload.instance
+        )
+        .fetch_optional(&mut transaction)
+        .await?;
+        if blog.is_none() {
+            return Err(BlogPostMutationError::InvalidTaxonomyTerm);
+        }
+
+        sqlx::query!(
+            r#"
+                INSERT INTO uuid (trashed, discriminator)
+                    VALUES (0, 'blogPost')
+            "#
+        )
+        .execute(&mut transaction)
+        .await?;
+        let value = sqlx::query!(r#"SELECT LAST_INSERT_ID() as id"#)
+            .fetch_one(&mut transaction)
+            .await?;
+        let id = value.id as i32;
+
+        sqlx::query!(
+            r#"
+                INSERT INTO blog_post (id, author_id, category_id, instance_id, title, content, date, publish)
+                    SELECT ?, ?, ?, i.id, ?, ?, ?, ?
+                    FROM instance i
+                    WHERE i.subdomain = ?
+            "#,
+            id,
+            payload.user_id,
+            payload.taxonomy_term_id,
+            payload.title,
+            payload.content,
+            DateTime::now(),
+            payload.publish_date,
+            payload.instance
+        )
+        .execute(&mut transaction)
+        .await?;
+
+        CreateTaxonomyLinkEventPayload::new(
+            payload.taxonomy_term_id,
+            id,
+            payload.user_id,
+            payload.instance,
+        )
+        .save(&mut transaction)
+        .await?;
+
+        Subscription::auto_subscribe(
+            &[id],
+            payload.user_id,
+            payload.subscribe,
+            payload.send_email,
+            &mut transaction,
+        )
+        .await?;
+
+        let blog_post = BlogPost::fetch_via_transaction(id, &mut transaction).await?;
+
+        transaction.commit().await?;
+
+        Ok(blog_post)
+    }
+
+    /// Updates the blog post. The legacy event log has no event type for changes of a blog post, so
+    /// (unlike the creation) the update isn't recorded and no author is needed.
+    pub async fn update<'a, E>(
+        payload: BlogPostUpdatePayload,
+        executor: E,
+    ) -> Result<Uuid, BlogPostMutationError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        let blog = sqlx::query!(r#"SELECT id FROM blog_post WHERE id = ?"#, payload.id)
+            .fetch_optional(&mut transaction)
+            .await?;
+        if blog.is_none() {
+            return Err(BlogPostMutationError::NotFound);
+        }
+
+        sqlx::query!(
+            r#"
+                UPDATE blog_post
+                    SET title = ?, content = ?, publish = ?
+                    WHERE id = ?
+            "#,
+            payload.title,
+            payload.content,
+            payload.publish_date,
+            payload.id
+        )
+        .execute(&mut transaction)
+        .await?;
+
+        let blog_post = BlogPost::fetch_via_transaction(payload.id, &mut transaction).await?;
+
+        transaction.commit().await?;
+
+        Ok(blog_post)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        BlogPost, BlogPostCreatePayload, BlogPostMutationError, BlogPostUpdatePayload, BlogPosts,
+    };
+    use crate::create_database_pool;
+    use crate::datetime::DateTime;
+    use crate::instance::Instance;
+    use crate::uuid::ConcreteUuid;
+
+    async fn fetch_blog_id<'a>(transaction: &mut sqlx::Transaction<'a, sqlx::MySql>) -> i32 {
+        sqlx::query!(
+            r#"
+                SELECT tt.id
+                    FROM term_taxonomy tt
+                    JOIN taxonomy t ON t.id = tt.taxonomy_id
+                    JOIN type ty ON ty.id = t.type_id
+                    JOIN instance i ON i.id = t.instance_id
+                    WHERE ty.name = 'blog' AND i.subdomain = 'de'
+                    LIMIT 1
+            "#
+        )
+        .fetch_one(transaction)
+        .await
+        .unwrap()
+        .id as i32
+    }
+
+    #[actix_rt::test]
+    async fn blog_posts_are_published_and_sorted() {
+        let pool = create_database_pool().await.unwrap();
+
+        let blog_posts = BlogPosts::fetch(Instance::De, 5, None, false, &pool)
+            .await
+            .unwrap()
+            .blog_posts;
+
+        assert!(blog_posts.windows(2).all(|pair| pair[0].id > pair[1].id));
+        for blog_post in blog_posts.iter() {
+            if let ConcreteUuid::BlogPost(blog_post) = &blog_post.concrete_uuid {
+                assert!(blog_post.publish_date.is_some());
+            } else {
+                panic!("UUID is not a blog post")
+            }
+        }
+    }
+
+    #[actix_rt::test]
+    async fn create_and_update_blog_post() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        let taxonomy_term_id = fetch_blog_id(&mut transaction).await;
+        let blog_post = BlogPost::create(
+            BlogPostCreatePayload {
+                instance: Instance::De,
+                user_id: 1,
+                taxonomy_term_id,
+                title: "Neuer Blogpost".to_string(),
+                content: "Inhalt".to_string(),
+                publish_date: None,
+                subscribe: true,
+                send_email: false,
+            },
+            &mut transaction,
+        )
+        .await
+        .unwrap();
+
+        let published =
+            BlogPosts::fetch_via_transaction(Instance::De, 1, None, false, &mut transaction)
+                .await
+                .unwrap()
+                .blog_posts;
+        assert!(published.iter().all(|uuid| uuid.id != blog_post.id));
+        let all = BlogPosts::fetch_via_transaction(Instance::De, 1, None, true, &mut transaction)
+            .await
+            .unwrap()
+            .blog_posts;
+        assert_eq!(all[0].id, blog_post.id);
+
+        let event = sqlx::query!(
+            r#"
+                SELECT e.name, l.uuid_id
+                    FROM event_log l
+                    JOIN event e ON e.id = l.event_id
+                    JOIN event_parameter p ON p.log_id = l.id
+                    JOIN event_parameter_uuid pu ON pu.event_parameter_id = p.id
+                    WHERE pu.uuid_id = ?
+            "#,
+            blog_post.id
+        )
+        .fetch_one(&mut transaction)
+        .await
+        .unwrap();
+        assert_eq!(event.name, "taxonomy/term/associate");
+        assert_eq!(event.uuid_id as i32, taxonomy_term_id);
+
+        let updated = BlogPost::update(
+            BlogPostUpdatePayload {
+                id: blog_post.id,
+                title: "Neuer Titel".to_string(),
+                content: "Neuer Inhalt".to_string(),
+                publish_date: Some(DateTime::ymd(2020, 1, 1)),
+            },
+            &mut transaction,
+        )
+        .await
+        .unwrap();
+
+        if let ConcreteUuid::BlogPost(updated) = updated.concrete_uuid {
+            assert_eq!(updated.title, "Neuer Titel");
+            assert_eq!(updated.taxonomy_term_id, taxonomy_term_id);
+            assert!(updated.publish_date.is_some());
+        } else {
+            panic!("UUID is not a blog post")
+        }
+    }
+
+    #[actix_rt::test]
+    async fn create_blog_post_fails_for_non_blog_taxonomy_term() {
+        let pool = create_database_pool().await.unwrap();
+
+        let result = BlogPost::create(
+            BlogPostCreatePayload {
+                instance: Instance::De,
+                user_id: 1,
+                taxonomy_term_id: 1,
+                title: "Neuer Blogpost".to_string(),
+                content: "Inhalt".to_string(),
+                publish_date: None,
+                subscribe: true,
+                send_email: false,
+            },
+            &pool,
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(BlogPostMutationError::InvalidTaxonomyTerm)
+        ));
+    }
 }
@@ -7,6 +7,7 @@ use super::{ConcreteUuid, Uuid, UuidError, UuidFetcher};
 use crate::database::Executor;
 use crate::datetime::DateTime;
 use crate::format_alias;
+use crate::instance::Instance;
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -26,10 +27,11 @@ macro_rules! fetch_one_comment {
     ($id: expr, $executor: expr) => {
        sqlx::query!(
             r#"
-                SELECT u.trashed, c.author_id, c.title, c.date, c.archived, c.content, c.parent_id, c.uuid_id, p.title as parent_title
+                SELECT u.trashed, i.subdomain, c.author_id, c.title, c.date, c.archived, c.content, c.parent_id, c.uuid_id, p.title as parent_title
                     FROM comment c
                     LEFT JOIN comment p ON p.id = c.parent_id
                     JOIN uuid u ON u.id = c.id
+                    JOIN instance i ON i.id = c.instance_id
                     WHERE c.id = ?
             "#,
             $id
@@ -60,11 +62,16 @@ macro_rules! to_comment {
         })?;
         let children = $children?;
         let context = $context?;
+        let instance: Instance = comment
+            .subdomain
+            .parse()
+            .map_err(|_| UuidError::InvalidInstance)?;
 
         Ok(Uuid {
             id: $id,
             trashed: comment.trashed != 0,
             alias: format_alias(
+                Some(&instance),
                 context.as_deref(),
                 $id,
                 Some(
@@ -0,0 +1,285 @@
+use serde::Serialize;
+use sqlx::MySqlPool;
+
+use super::{Entity, EntityType};
+use crate::database::{placeholders, Executor};
+use crate::datetime::DateTime;
+use crate::instance::Instance;
+use crate::uuid::{Uuid, UuidError, UuidFetcher};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Entities {
+    pub entity_ids: Vec<i32>,
+    /// The entities themselves, only included if requested. Entities that cannot be fetched (e.g.
+    /// because their type is not supported) are left out, their ids are still in `entity_ids`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entities: Option<Vec<Uuid>>,
+}
+
+#[derive(Debug, Default)]
+pub struct EntitiesFilter {
+    pub entity_type: Option<EntityType>,
+    pub instance: Option<Instance>,
+    /// Only entities that are directly linked to this taxonomy term or one of its descendants.
+    pub taxonomy_term_id: Option<i32>,
+    pub license_id: Option<i32>,
+    pub trashed: Option<bool>,
+    pub has_current_revision: Option<bool>,
+    pub created_after: Option<DateTime>,
+    pub created_before: Option<DateTime>,
+}
+
+impl Entities {
+    pub async fn fetch(
+        filter: EntitiesFilter,
+        first: i32,
+        after: Option<i32>,
+        include_entities: bool,
+        pool: &MySqlPool,
+    ) -> Result<Self, UuidError> {
+        Self::fetch_via_transaction(filter, first, after, include_entities, pool).await
+    }
+
+    /// Fetches the ids of the first `first` entities matching the filter whose id is greater than
+    /// `after`, ordered by id.
+    pub async fn fetch_via_transaction<'a, E>(
+        filter: EntitiesFilter,
+        first: i32,
+        after: Option<i32>,
+        include_entities: bool,
+        executor: E,
+    ) -> Result<Self, UuidError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        let taxonomy_term_ids = match filter.taxonomy_term_id {
+            Some(id) => Some(fetch_taxonomy_term_descendants(id, &mut transaction).await?),
+            None => None,
+        };
+        let taxonomy_term_condition = match &taxonomy_term_ids {
+            Some(ids) => format!(
+                "AND EXISTS (SELECT tte.entity_id FROM term_taxonomy_entity tte WHERE tte.entity_id = e.id AND tte.term_taxonomy_id IN ({}))",
+                placeholders(ids.len())
+            ),
+            None => "".to_string(),
+        };
+        let query = format!(
+            r#"
+                SELECT e.id
+                    FROM entity e
+                    JOIN uuid u ON u.id = e.id
+                    JOIN instance i ON i.id = e.instance_id
+                    JOIN type t ON t.id = e.type_id
+                    WHERE e.id > ?
+                        AND (? IS NULL OR t.name = ?)
+                        AND (? IS NULL OR i.subdomain = ?)
+                        AND (? IS NULL OR e.license_id = ?)
+                        AND (? IS NULL OR u.trashed = ?)
+                        AND (? IS NULL OR (e.current_revision_id IS NOT NULL) = ?)
+                        AND (? IS NULL OR e.date > ?)
+                        AND (? IS NULL OR e.date < ?)
+                        {}
+                    ORDER BY e.id
+                    LIMIT ?
+            "#,
+            taxonomy_term_condition
+        );
+        let mut query = sqlx::query_as::<_, (i64,)>(&query)
+            .bind(after.unwrap_or(0))
+            .bind(filter.entity_type.clone())
+            .bind(filter.entity_type)
+            .bind(filter.instance.clone())
+            .bind(filter.instance)
+            .bind(filter.license_id)
+            .bind(filter.license_id)
+            .bind(filter.trashed)
+            .bind(filter.trashed)
+            .bind(filter.has_current_revision)
+            .bind(filter.has_current_revision)
+            .bind(filter.created_after.clone())
+            .bind(filter.created_after)
+            .bind(filter.created_before.clone())
+            .bind(filter.created_before);
+        if let Some(ids) = taxonomy_term_ids {
+            for id in ids {
+                query = query.bind(id);
+            }
+        }
+        let entity_ids: Vec<i32> = query
+            .bind(first)
+            .fetch_all(&mut transaction)
+            .await?
+            .into_iter()
+            .map(|(id,)| id as i32)
+            .collect();
+
+        let entities = if include_entities {
+            let mut entities = Vec::with_capacity(entity_ids.len());
+            for id in entity_ids.iter() {
+                match Entity::fetch_via_transaction(*id, &mut transaction).await {
+                    Ok(entity) => entities.push(entity),
+                    Err(UuidError::UnsupportedEntityType { .. })
+                    | Err(UuidError::UnsupportedEntityRevisionType { .. })
+                    | Err(UuidError::EntityMissingRequiredParent) => {}
+                    Err(error) => return Err(error),
+                }
+            }
+            Some(entities)
+        } else {
+            None
+        };
+
+        transaction.commit().await?;
+
+        Ok(Entities {
+            entity_ids,
+            entities,
+        })
+    }
+}
+
+/// Returns the id of the taxonomy term together with the ids of all its descendants.
+async fn fetch_taxonomy_term_descendants<'a, E>(id: i32, executor: E) -> Result<Vec<i32>, UuidError>
+where
+    E: Executor<'a>,
+{
+    let mut transaction = executor.begin().await?;
+
+    let mut ids = vec![id];
+    let mut parent_ids = vec![id];
+    while !parent_ids.is_empty() {
+        let query = format!(
+            "SELECT id FROM term_taxonomy WHERE parent_id IN ({})",
+            placeholders(parent_ids.len())
+        );
+        let mut query = sqlx::query_as::<_, (i64,)>(&query);
+        for parent_id in parent_ids.iter() {
+            query = query.bind(parent_id);
+        }
+        parent_ids = query
+            .fetch_all(&mut transaction)
+            .await?
+            .into_iter()
+            .map(|(id,)| id as i32)
+            .filter(|id| !ids.contains(id))
+            .collect();
+        ids.extend(parent_ids.iter());
+    }
+
+    transaction.commit().await?;
+
+    Ok(ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Entities, EntitiesFilter};
+    use crate::create_database_pool;
+    use crate::instance::Instance;
+    use crate::uuid::{ConcreteUuid, EntityType};
+
+    #[actix_rt::test]
+    async fn entities() {
+        let pool = create_database_pool().await.unwrap();
+
+        let entities = Entities::fetch(EntitiesFilter::default(), 5, None, false, &pool)
+            .await
+            .unwrap();
+
+        assert_eq!(entities.entity_ids.len(), 5);
+        assert!(entities.entities.is_none());
+        assert!(entities.entity_ids.windows(2).all(|ids| ids[0] < ids[1]));
+
+        let last_id = *entities.entity_ids.last().unwrap();
+        let next = Entities::fetch(EntitiesFilter::default(), 5, Some(last_id), false, &pool)
+            .await
+            .unwrap();
+        assert!(next.entity_ids.iter().all(|id| *id > last_id));
+    }
+
+    #[actix_rt::test]
+    async fn entities_of_all_types() {
+        let pool = create_database_pool().await.unwrap();
+
+        let entities = Entities::fetch(EntitiesFilter::default(), 1000, None, true, &pool)
+            .await
+            .unwrap();
+
+        let uuids = entities.entities.unwrap();
+        assert!(!uuids.is_empty());
+        assert!(uuids
+            .iter()
+            .all(|uuid| entities.entity_ids.contains(&uuid.id)));
+    }
+
+    #[actix_rt::test]
+    async fn entities_with_filters() {
+        let pool = create_database_pool().await.unwrap();
+
+        let entities = Entities::fetch(
+            EntitiesFilter {
+                entity_type: Some(EntityType::Article),
+                instance: Some(Instance::De),
+                trashed: Some(false),
+                has_current_revision: Some(true),
+                ..EntitiesFilter::default()
+            },
+            5,
+            None,
+            true,
+            &pool,
+        )
+        .await
+        .unwrap();
+
+        let uuids = entities.entities.unwrap();
+        assert_eq!(uuids.len(), entities.entity_ids.len());
+        for uuid in uuids.iter() {
+            assert!(!uuid.trashed);
+            if let ConcreteUuid::Entity(entity) = &uuid.concrete_uuid {
+                assert_eq!(entity.abstract_entity.__typename, EntityType::Article);
+                assert_eq!(entity.abstract_entity.instance, Instance::De);
+                assert!(entity.abstract_entity.current_revision_id.is_some());
+            } else {
+                panic!("UUID is not an entity")
+            }
+        }
+    }
+
+    #[actix_rt::test]
+    async fn entities_of_taxonomy_term_include_descendants() {
+        let pool = create_database_pool().await.unwrap();
+
+        let link = sqlx::query!(
+            r#"
+                SELECT tte.entity_id, tt.parent_id
+                    FROM term_taxonomy_entity tte
+                    JOIN term_taxonomy tt ON tt.id = tte.term_taxonomy_id
+                    WHERE tt.parent_id IS NOT NULL
+                    LIMIT 1
+            "#
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let entity_id = link.entity_id as i32;
+
+        let entities = Entities::fetch(
+            EntitiesFilter {
+                taxonomy_term_id: link.parent_id.map(|id| id as i32),
+                ..EntitiesFilter::default()
+            },
+            1,
+            Some(entity_id - 1),
+            false,
+            &pool,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(entities.entity_ids, vec![entity_id]);
+    }
+}
@@ -1,18 +1,25 @@
+use std::collections::HashMap;
+
 use async_trait::async_trait;
 use futures::try_join;
 use serde::Serialize;
 use sqlx::MySqlPool;
 
 use abstract_entity::AbstractEntity;
+pub use entities::{Entities, EntitiesFilter};
 pub use entity_type::EntityType;
+pub use unrevised_revisions::{UnrevisedEntity, UnrevisedRevision, UnrevisedRevisions};
 
 use super::taxonomy_term::TaxonomyTerm;
 use super::{ConcreteUuid, Uuid, UuidError, UuidFetcher};
-use crate::database::Executor;
+use crate::database::{fetch_all_by_ids, Executor};
 use crate::format_alias;
+use crate::instance::Instance;
 
 mod abstract_entity;
+mod entities;
 mod entity_type;
+mod unrevised_revisions;
 
 #[derive(Debug, Serialize)]
 pub struct Entity {
@@ -75,16 +82,13 @@ macro_rules! fetch_one_entity {
     ($id: expr, $executor: expr) => {
         sqlx::query!(
             r#"
-                SELECT t.name, u.trashed, i.subdomain, e.date, e.current_revision_id, e.license_id, f1.value as title, f2.value as fallback_title
+                SELECT t.name, u.trashed, i.subdomain, e.date, e.current_revision_id, e.license_id
                     FROM entity e
                     JOIN uuid u ON u.id = e.id
                     JOIN instance i ON i.id = e.instance_id
                     JOIN type t ON t.id = e.type_id
-                    LEFT JOIN entity_revision_field f1 ON f1.entity_revision_id = e.current_revision_id AND f1.field = 'title'
-                    LEFT JOIN entity_revision_field f2 on f2.entity_revision_id = (SELECT id FROM entity_revision WHERE repository_id = ? LIMIT 1) AND f2.field = 'title'
                     WHERE e.id = ?
             "#,
-            $id,
             $id
         )
         .fetch_one($executor)
@@ -112,7 +116,7 @@ macro_rules! fetch_all_taxonomy_terms_parents {
 }
 
 macro_rules! to_entity {
-    ($id: expr, $entity: expr, $revisions: expr, $taxonomy_terms: expr, $subject: expr, $executor: expr) => {{
+    ($id: expr, $entity: expr, $revisions: expr, $taxonomy_terms: expr, $title: expr, $subject: expr, $executor: expr) => {{
         let abstract_entity = AbstractEntity {
             __typename: $entity.name.parse::<EntityType>()?,
             instance: $entity
@@ -181,17 +185,7 @@ macro_rules! to_entity {
         Ok(Uuid {
             id: $id,
             trashed: $entity.trashed != 0,
-            alias: format_alias(
-                $subject.as_deref(),
-                $id,
-                Some(
-                    $entity
-                        .title
-                        .or($entity.fallback_title)
-                        .unwrap_or(format!("{}", $id))
-                        .as_str(),
-                ),
-            ),
+            alias: Entity::get_alias($id, &abstract_entity.instance, $subject.as_deref(), &$title),
             concrete_uuid: ConcreteUuid::Entity(Entity {
                 abstract_entity,
                 concrete_entity,
@@ -200,51 +194,18 @@ macro_rules! to_entity {
     }};
 }
 
-macro_rules! fetch_all_taxonomy_terms_ancestors {
-    ($id: expr, $executor: expr) => {
-        sqlx::query!(
-            r#"
-                SELECT term_taxonomy_id as id
-                    FROM (
-                        SELECT term_taxonomy_id, entity_id FROM term_taxonomy_entity
-                        UNION ALL
-                        SELECT t.term_taxonomy_id, l.child_id as entity_id
-                            FROM term_taxonomy_entity t
-                            JOIN entity_link l ON t.entity_id = l.parent_id
-                        UNION ALL
-                        SELECT t.term_taxonomy_id, l2.child_id as entity_id
-                            FROM term_taxonomy_entity t
-                            JOIN entity_link l1 ON t.entity_id = l1.parent_id
-                            JOIN entity_link l2 ON l2.parent_id = l1.child_id
-                    ) u
-                    WHERE entity_id = ?
-            "#,
-            $id
-        )
-        .fetch_all($executor)
-    };
-}
-
-macro_rules! fetch_canonical_subject {
-    ($taxonomy_terms: expr, $executor: expr) => {
-        match $taxonomy_terms.first() {
-            Some(term) => TaxonomyTerm::fetch_canonical_subject(term.id as i32, $executor).await?,
-            _ => None,
-        }
-    };
-}
-
 #[async_trait]
 impl UuidFetcher for Entity {
     async fn fetch(id: i32, pool: &MySqlPool) -> Result<Uuid, UuidError> {
         let entity = fetch_one_entity!(id, pool);
         let revisions = fetch_all_revisions!(id, pool);
         let taxonomy_terms = fetch_all_taxonomy_terms_parents!(id, pool);
+        let title = Entity::fetch_title(id, pool);
         let subject = Entity::fetch_canonical_subject(id, pool);
-        let (entity, revisions, taxonomy_terms, subject) =
-            try_join!(entity, revisions, taxonomy_terms, subject)?;
+        let (entity, revisions, taxonomy_terms, title, subject) =
+            try_join!(entity, revisions, taxonomy_terms, title, subject)?;
 
-        to_entity!(id, entity, revisions, taxonomy_terms, subject, pool)
+        to_entity!(id, entity, revisions, taxonomy_terms, title, subject, pool)
     }
 
     async fn fetch_via_transaction<'a, E>(id: i32, executor: E) -> Result<Uuid, UuidError>
@@ -255,12 +216,14 @@ impl UuidFetcher for Entity {
         let entity = fetch_one_entity!(id, &mut transaction).await?;
         let revisions = fetch_all_revisions!(id, &mut transaction).await?;
         let taxonomy_terms = fetch_all_taxonomy_terms_parents!(id, &mut transaction).await?;
+        let title = Entity::fetch_title(id, &mut transaction).await?;
         let subject = Entity::fetch_canonical_subject_via_transaction(id, &mut transaction).await?;
         let result = to_entity!(
             id,
             entity,
             revisions,
             taxonomy_terms,
+            title,
             subject,
             &mut transaction
         );
@@ -270,27 +233,209 @@ impl UuidFetcher for Entity {
 }
 
 impl Entity {
+    fn get_alias(id: i32, instance: &Instance, subject: Option<&str>, title: &str) -> String {
+        format_alias(Some(instance), subject, id, Some(title))
+    }
+
+    /// Fetches the aliases of the given entities without loading the entities themselves.
+    /// Entities that do not exist are left out.
+    pub async fn fetch_aliases_via_transaction<'a, E>(
+        ids: &[i32],
+        executor: E,
+    ) -> Result<HashMap<i32, String>, UuidError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        let entities: Vec<(i64, String)> = fetch_all_by_ids(
+            r#"
+                SELECT e.id, i.subdomain
+                    FROM entity e
+                    JOIN instance i ON i.id = e.instance_id
+                    WHERE e.id IN ({})
+            "#,
+            ids,
+            &mut transaction,
+        )
+        .await?;
+        let mut titles = Self::fetch_titles_via_transaction(ids, &mut transaction).await?;
+        let mut subjects =
+            Self::fetch_canonical_subjects_via_transaction(ids, &mut transaction).await?;
+
+        transaction.commit().await?;
+
+        entities
+            .into_iter()
+            .map(|(id, subdomain)| {
+                let id = id as i32;
+                let instance: Instance =
+                    subdomain.parse().map_err(|_| UuidError::InvalidInstance)?;
+                let title = titles.remove(&id).unwrap_or_else(|| id.to_string());
+                let subject = subjects.remove(&id).flatten();
+                Ok((
+                    id,
+                    Self::get_alias(id, &instance, subject.as_deref(), &title),
+                ))
+            })
+            .collect()
+    }
+
+    async fn fetch_title<'a, E>(id: i32, executor: E) -> Result<String, sqlx::Error>
+    where
+        E: Executor<'a>,
+    {
+        Ok(Self::fetch_titles_via_transaction(&[id], executor)
+            .await?
+            .remove(&id)
+            .unwrap_or_else(|| id.to_string()))
+    }
+
+    /// Fetches the titles used in the aliases of the given entities: the title of the current
+    /// revision or, if there is none, the title of the first revision.
+    async fn fetch_titles_via_transaction<'a, E>(
+        ids: &[i32],
+        executor: E,
+    ) -> Result<HashMap<i32, String>, sqlx::Error>
+    where
+        E: Executor<'a>,
+    {
+        let titles: Vec<(i64, Option<String>, Option<String>)> = fetch_all_by_ids(
+            r#"
+                SELECT e.id, f1.value as title, f2.value as fallback_title
+                    FROM entity e
+                    LEFT JOIN entity_revision_field f1 ON f1.entity_revision_id = e.current_revision_id AND f1.field = 'title'
+                    LEFT JOIN entity_revision_field f2 on f2.entity_revision_id = (SELECT id FROM entity_revision WHERE repository_id = e.id LIMIT 1) AND f2.field = 'title'
+                    WHERE e.id IN ({})
+            "#,
+            ids,
+            executor,
+        )
+        .await?;
+        Ok(titles
+            .into_iter()
+            .filter_map(|(id, title, fallback_title)| {
+                title.or(fallback_title).map(|title| (id as i32, title))
+            })
+            .collect())
+    }
+
     pub async fn fetch_canonical_subject(
         id: i32,
         pool: &MySqlPool,
     ) -> Result<Option<String>, sqlx::Error> {
-        let taxonomy_terms = fetch_all_taxonomy_terms_ancestors!(id, pool).await?;
-        let subject = fetch_canonical_subject!(taxonomy_terms, pool);
-        Ok(subject)
+        Self::fetch_canonical_subject_via_transaction(id, pool).await
     }
 
     pub async fn fetch_canonical_subject_via_transaction<'a, E>(
         id: i32,
         executor: E,
     ) -> Result<Option<String>, sqlx::Error>
+    where
+        E: Executor<'a>,
+    {
+        Ok(
+            Self::fetch_canonical_subjects_via_transaction(&[id], executor)
+                .await?
+                .remove(&id)
+                .flatten(),
+        )
+    }
+
+    /// Fetches the canonical subjects of the given entities, which are determined by the first
+    /// taxonomy term they are linked to (see [`Entity::fetch_taxonomy_terms_ancestors_via_transaction`]).
+    pub async fn fetch_canonical_subjects_via_transaction<'a, E>(
+        ids: &[i32],
+        executor: E,
+    ) -> Result<HashMap<i32, Option<String>>, sqlx::Error>
     where
         E: Executor<'a>,
     {
         let mut transaction = executor.begin().await?;
-        let taxonomy_terms = fetch_all_taxonomy_terms_ancestors!(id, &mut transaction).await?;
-        let subject = fetch_canonical_subject!(taxonomy_terms, &mut transaction);
+
+        let taxonomy_terms =
+            Self::fetch_all_taxonomy_terms_ancestors_via_transaction(ids, &mut transaction).await?;
+        let term_ids: Vec<i32> = taxonomy_terms
+            .values()
+            .filter_map(|terms| terms.first())
+            .copied()
+            .collect();
+        let subjects_by_taxonomy_term =
+            TaxonomyTerm::fetch_canonical_subjects_via_transaction(&term_ids, &mut transaction)
+                .await?;
+        let subjects = ids
+            .iter()
+            .map(|id| {
+                let subject = taxonomy_terms
+                    .get(id)
+                    .and_then(|terms| terms.first())
+                    .and_then(|term_id| subjects_by_taxonomy_term.get(term_id))
+                    .cloned()
+                    .flatten();
+                (*id, subject)
+            })
+            .collect();
+
         transaction.commit().await?;
-        Ok(subject)
+
+        Ok(subjects)
+    }
+
+    /// Fetches the ids of the taxonomy terms the entity is linked to, either directly or via its
+    /// parents (e.g. the exercise group of a grouped exercise).
+    pub async fn fetch_taxonomy_terms_ancestors_via_transaction<'a, E>(
+        id: i32,
+        executor: E,
+    ) -> Result<Vec<i32>, sqlx::Error>
+    where
+        E: Executor<'a>,
+    {
+        Ok(
+            Self::fetch_all_taxonomy_terms_ancestors_via_transaction(&[id], executor)
+                .await?
+                .remove(&id)
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Like [`Entity::fetch_taxonomy_terms_ancestors_via_transaction`] for many entities. Direct
+    /// links come first, then the links of the parents and grandparents.
+    async fn fetch_all_taxonomy_terms_ancestors_via_transaction<'a, E>(
+        ids: &[i32],
+        executor: E,
+    ) -> Result<HashMap<i32, Vec<i32>>, sqlx::Error>
+    where
+        E: Executor<'a>,
+    {
+        let links: Vec<(i64, i64)> = fetch_all_by_ids(
+            r#"
+                SELECT entity_id, term_taxonomy_id
+                    FROM (
+                        SELECT term_taxonomy_id, entity_id FROM term_taxonomy_entity
+                        UNION ALL
+                        SELECT t.term_taxonomy_id, l.child_id as entity_id
+                            FROM term_taxonomy_entity t
+                            JOIN entity_link l ON t.entity_id = l.parent_id
+                        UNION ALL
+                        SELECT t.term_taxonomy_id, l2.child_id as entity_id
+                            FROM term_taxonomy_entity t
+                            JOIN entity_link l1 ON t.entity_id = l1.parent_id
+                            JOIN entity_link l2 ON l2.parent_id = l1.child_id
+                    ) u
+                    WHERE entity_id IN ({})
+            "#,
+            ids,
+            executor,
+        )
+        .await?;
+        let mut taxonomy_terms: HashMap<i32, Vec<i32>> = HashMap::new();
+        for (entity_id, term_id) in links {
+            taxonomy_terms
+                .entry(entity_id as i32)
+                .or_insert_with(Vec::new)
+                .push(term_id as i32);
+        }
+        Ok(taxonomy_terms)
     }
 
     async fn find_parent_by_id<'a, E>(id: i32, executor: E) -> Result<i32, UuidError>
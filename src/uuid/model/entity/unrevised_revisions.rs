@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use serde::Serialize;
+use sqlx::MySqlPool;
+
+use super::{Entity, EntityType};
+use crate::database::{placeholders, Executor};
+use crate::datetime::DateTime;
+use crate::event::RawEventType;
+use crate::instance::Instance;
+use crate::uuid::UuidError;
+
+/// Maximum number of batches of `first` entities that are looked at for one page of the queue.
+const MAX_BATCHES: usize = 10;
+
+/// The review queue: entities with revisions that are newer than their current revision.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnrevisedRevisions {
+    pub entities: Vec<UnrevisedEntity>,
+    /// The id to pass as `after` to continue with the queue, `None` if the end of the queue has
+    /// been reached. With a subject filter, fewer than `first` entities may be returned even if
+    /// the queue continues.
+    pub next_after: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnrevisedEntity {
+    pub id: i32,
+    #[serde(rename(serialize = "__typename"))]
+    pub __typename: EntityType,
+    pub current_revision_id: Option<i32>,
+    pub subject: Option<String>,
+    pub revisions: Vec<UnrevisedRevision>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnrevisedRevision {
+    pub id: i32,
+    pub author_id: i32,
+    pub date: DateTime,
+}
+
+impl UnrevisedRevisions {
+    pub async fn fetch(
+        instance: Instance,
+        subject: Option<String>,
+        first: i32,
+        after: Option<i32>,
+        pool: &MySqlPool,
+    ) -> Result<Self, UuidError> {
+        Self::fetch_via_transaction(instance, subject, first, after, pool).await
+    }
+
+    /// Fetches the first `first` entities (ordered by id, with an id greater than `after`) that
+    /// have unrevised revisions. Revisions that are trashed or have been rejected are no longer
+    /// part of the queue. If `subject` is given, only entities with this canonical subject are
+    /// returned, looking at no more than `MAX_BATCHES * first` entities of the queue.
+    pub async fn fetch_via_transaction<'a, E>(
+        instance: Instance,
+        subject: Option<String>,
+        first: i32,
+        after: Option<i32>,
+        executor: E,
+    ) -> Result<Self, UuidError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        let first = first.max(0) as usize;
+        let mut cursor = after.unwrap_or(0);
+        let mut complete = false;
+        let mut entities: Vec<UnrevisedEntity> = Vec::new();
+
+        // The subjects can't be computed in SQL, so we look at a bounded number of batches of
+        // `first` entities until enough of them have the requested subject.
+        for _ in 0..MAX_BATCHES {
+            if entities.len() >= first {
+                break;
+            }
+
+            let candidates = sqlx::query!(
+                r#"
+                    SELECT e.id, e.current_revision_id, t.name
+                        FROM entity e
+                        JOIN uuid ue ON ue.id = e.id
+                        JOIN instance i ON i.id = e.instance_id
+                        JOIN type t ON t.id = e.type_id
+                        WHERE i.subdomain = ?
+                            AND e.id > ?
+                            AND ue.trashed = 0
+                            AND EXISTS (
+                                SELECT r.id
+                                    FROM entity_revision r
+                                    JOIN uuid ur ON ur.id = r.id
+                                    WHERE r.repository_id = e.id
+                                        AND ur.trashed = 0
+                                        AND (e.current_revision_id IS NULL OR r.id > e.current_revision_id)
+                                        AND NOT EXISTS (
+                                            SELECT l.id
+                                                FROM event_log l
+                                                JOIN event ev ON ev.id = l.event_id
+                                                WHERE l.uuid_id = r.id AND ev.name = ?
+                                        )
+                            )
+                        ORDER BY e.id
+                        LIMIT ?
+                "#,
+                instance,
+                cursor,
+                RawEventType::RejectRevision,
+                first as i64
+            )
+            .fetch_all(&mut transaction)
+            .await?;
+            let exhausted = candidates.len() < first;
+
+            let ids: Vec<i32> = candidates
+                .iter()
+                .map(|candidate| candidate.id as i32)
+                .collect();
+            let mut subjects =
+                Entity::fetch_canonical_subjects_via_transaction(&ids, &mut transaction).await?;
+
+            let mut consumed = true;
+            for candidate in candidates {
+                if entities.len() >= first {
+                    consumed = false;
+                    break;
+                }
+                cursor = candidate.id as i32;
+
+                // Entity types that we don't support yet cannot be reviewed here.
+                let __typename = match candidate.name.parse::<EntityType>() {
+                    Ok(__typename) => __typename,
+                    Err(_) => continue,
+                };
+                let entity_subject = subjects.remove(&cursor).flatten();
+                if subject.is_none() || entity_subject == subject {
+                    entities.push(UnrevisedEntity {
+                        id: cursor,
+                        __typename,
+                        current_revision_id: candidate.current_revision_id,
+                        subject: entity_subject,
+                        revisions: Vec::new(),
+                    });
+                }
+            }
+
+            if exhausted && consumed {
+                complete = true;
+                break;
+            }
+        }
+
+        let next_after = if complete { None } else { Some(cursor) };
+
+        if !entities.is_empty() {
+            let query = format!(
+                r#"
+                    SELECT r.id, r.author_id, r.date, r.repository_id
+                        FROM entity_revision r
+                        JOIN uuid ur ON ur.id = r.id
+                        JOIN entity e ON e.id = r.repository_id
+                        WHERE r.repository_id IN ({})
+                            AND ur.trashed = 0
+                            AND (e.current_revision_id IS NULL OR r.id > e.current_revision_id)
+                            AND NOT EXISTS (
+                                SELECT l.id
+                                    FROM event_log l
+                                    JOIN event ev ON ev.id = l.event_id
+                                    WHERE l.uuid_id = r.id AND ev.name = ?
+                            )
+                        ORDER BY r.id
+                "#,
+                placeholders(entities.len())
+            );
+            let mut query = sqlx::query_as::<_, (i64, i64, chrono::DateTime<Utc>, i64)>(&query);
+            for entity in entities.iter() {
+                query = query.bind(entity.id);
+            }
+            let revisions = query
+                .bind(RawEventType::RejectRevision)
+                .fetch_all(&mut transaction)
+                .await?;
+
+            let mut indices: HashMap<i32, usize> = HashMap::new();
+            for (index, entity) in entities.iter().enumerate() {
+                indices.insert(entity.id, index);
+            }
+            for (id, author_id, date, entity_id) in revisions {
+                if let Some(index) = indices.get(&(entity_id as i32)) {
+                    entities[*index].revisions.push(UnrevisedRevision {
+                        id: id as i32,
+                        author_id: author_id as i32,
+                        date: date.into(),
+                    });
+                }
+            }
+        }
+
+        transaction.commit().await?;
+
+        Ok(UnrevisedRevisions {
+            entities,
+            next_after,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UnrevisedRevisions;
+    use crate::create_database_pool;
+    use crate::instance::Instance;
+
+    #[actix_rt::test]
+    async fn unrevised_revisions() {
+        let pool = create_database_pool().await.unwrap();
+
+        let entities = UnrevisedRevisions::fetch(Instance::De, None, 5, None, &pool)
+            .await
+            .unwrap()
+            .entities;
+
+        assert!(entities.len() <= 5);
+        assert!(entities
+            .windows(2)
+            .all(|entities| entities[0].id < entities[1].id));
+        for entity in entities.iter() {
+            assert!(!entity.revisions.is_empty());
+            for revision in entity.revisions.iter() {
+                assert!(entity
+                    .current_revision_id
+                    .map_or(true, |current_revision_id| revision.id
+                        > current_revision_id));
+            }
+        }
+    }
+
+    #[actix_rt::test]
+    async fn unrevised_revisions_of_subject() {
+        let pool = create_database_pool().await.unwrap();
+
+        let entity = UnrevisedRevisions::fetch(Instance::De, None, 1000, None, &pool)
+            .await
+            .unwrap()
+            .entities
+            .into_iter()
+            .find(|entity| entity.subject.is_some())
+            .expect("No unrevised entity with a subject");
+
+        let unrevised_revisions = UnrevisedRevisions::fetch(
+            Instance::De,
+            entity.subject.clone(),
+            1,
+            Some(entity.id - 1),
+            &pool,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(unrevised_revisions.entities.len(), 1);
+        assert_eq!(unrevised_revisions.entities[0].id, entity.id);
+        assert_eq!(unrevised_revisions.entities[0].subject, entity.subject);
+        assert_eq!(unrevised_revisions.next_after, Some(entity.id));
+    }
+}
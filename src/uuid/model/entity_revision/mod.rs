@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use async_trait::async_trait;
 use futures::try_join;
 use serde::Serialize;
@@ -14,7 +16,7 @@ use self::generic_entity_revision::GenericRevision;
 use self::video_revision::VideoRevision;
 use super::entity::Entity;
 use super::{ConcreteUuid, Uuid, UuidError, UuidFetcher};
-use crate::database::Executor;
+use crate::database::{fetch_all_by_ids, Executor};
 
 mod abstract_entity_revision;
 mod applet_revision;
@@ -132,10 +134,7 @@ macro_rules! to_entity_revisions {
         Ok(Uuid {
             id: $id,
             trashed: $revision.trashed != 0,
-            alias: format!(
-                "/entity/repository/compare/{}/{}",
-                $revision.repository_id, $id
-            ),
+            alias: EntityRevision::get_alias($id, $revision.repository_id as i32),
             concrete_uuid: ConcreteUuid::EntityRevision(EntityRevision {
                 abstract_entity_revision,
                 concrete_entity_revision,
@@ -166,6 +165,33 @@ impl UuidFetcher for EntityRevision {
 }
 
 impl EntityRevision {
+    fn get_alias(id: i32, repository_id: i32) -> String {
+        format!("/entity/repository/compare/{}/{}", repository_id, id)
+    }
+
+    /// Fetches the aliases of the given entity revisions without loading the revisions themselves.
+    /// Revisions that do not exist are left out.
+    pub async fn fetch_aliases_via_transaction<'a, E>(
+        ids: &[i32],
+        executor: E,
+    ) -> Result<HashMap<i32, String>, UuidError>
+    where
+        E: Executor<'a>,
+    {
+        let revisions: Vec<(i64, i64)> = fetch_all_by_ids(
+            r#"SELECT id, repository_id FROM entity_revision WHERE id IN ({})"#,
+            ids,
+            executor,
+        )
+        .await?;
+        Ok(revisions
+            .into_iter()
+            .map(|(id, repository_id)| {
+                (id as i32, Self::get_alias(id as i32, repository_id as i32))
+            })
+            .collect())
+    }
+
     pub async fn fetch_canonical_subject(
         id: i32,
         pool: &MySqlPool,
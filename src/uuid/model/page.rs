@@ -1,13 +1,18 @@
+use std::collections::HashMap;
+
 use async_trait::async_trait;
 use futures::join;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use sqlx::MySqlPool;
+use thiserror::Error;
 
 use super::{ConcreteUuid, Uuid, UuidError, UuidFetcher};
-use crate::database::Executor;
+use crate::database::{fetch_all_by_ids, Executor};
 use crate::datetime::DateTime;
+use crate::event::{CheckoutRevisionEventPayload, CreateEntityRevisionEventPayload, EventError};
 use crate::format_alias;
 use crate::instance::Instance;
+use crate::subscription::{Subscription, SubscriptionChangeError};
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -59,17 +64,17 @@ macro_rules! to_page {
         if revisions.is_empty() {
             Err(UuidError::NotFound)
         } else {
+            let instance: Instance = page
+                .subdomain
+                .parse()
+                .map_err(|_| UuidError::InvalidInstance)?;
             Ok(Uuid {
                 id: $id,
                 trashed: page.trashed != 0,
-                // TODO:
-                alias: format_alias(None, $id, page.title.as_deref()),
+                alias: Page::get_alias($id, &instance, page.title.as_deref()),
                 concrete_uuid: ConcreteUuid::Page(Page {
                     __typename: "Page".to_string(),
-                    instance: page
-                        .subdomain
-                        .parse()
-                        .map_err(|_| UuidError::InvalidInstance)?,
+                    instance,
                     current_revision_id: page.current_revision_id,
                     revision_ids: revisions
                         .iter()
@@ -109,3 +114,623 @@ impl UuidFetcher for Page {
         to_page!(id, page, revisions)
     }
 }
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageCreatePayload {
+    pub instance: Instance,
+    pub license_id: i32,
+    pub title: String,
+    pub content: String,
+    pub user_id: i32,
+    pub subscribe: bool,
+    pub send_email: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageAddRevisionPayload {
+    pub page_id: i32,
+    pub title: String,
+    pub content: String,
+    pub user_id: i32,
+    pub subscribe: bool,
+    pub send_email: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageCheckoutRevisionPayload {
+    pub revision_id: i32,
+    pub reason: String,
+    pub user_id: i32,
+}
+
+#[derive(Error, Debug)]
+pub enum PageMutationError {
+    #[error("Page cannot be changed because of a database error: {inner:?}.")]
+    DatabaseError { inner: sqlx::Error },
+    #[error("Page cannot be changed because of an event error: {inner:?}.")]
+    EventError { inner: EventError },
+    #[error("Page cannot be changed because of an uuid error: {inner:?}.")]
+    UuidError { inner: UuidError },
+    #[error("Page cannot be changed because the license does not exist in its instance.")]
+    InvalidLicense,
+    #[error("Page cannot be changed because its instance is invalid.")]
+    InvalidInstance,
+    #[error("Page cannot be changed because it does not exist.")]
+    PageNotFound,
+    #[error("PageRevision cannot be checked out because it does not exist.")]
+    RevisionNotFound,
+    #[error("PageRevision cannot be checked out because it is already the current revision.")]
+    RevisionAlreadyCheckedOut,
+}
+
+impl From<sqlx::Error> for PageMutationError {
+    fn from(inner: sqlx::Error) -> Self {
+        PageMutationError::DatabaseError { inner }
+    }
+}
+
+impl From<EventError> for PageMutationError {
+    fn from(error: EventError) -> Self {
+        match error {
+            EventError::DatabaseError { inner } => inner.into(),
+            inner => PageMutationError::EventError { inner },
+        }
+    }
+}
+
+impl From<SubscriptionChangeError> for PageMutationError {
+    fn from(error: SubscriptionChangeError) -> Self {
+        match error {
+            SubscriptionChangeError::DatabaseError { inner } => inner.into(),
+        }
+    }
+}
+
+impl From<UuidError> for PageMutationError {
+    fn from(error: UuidError) -> Self {
+        match error {
+            UuidError::DatabaseError { inner } => inner.into(),
+            inner => PageMutationError::UuidError { inner },
+        }
+    }
+}
+
+impl Page {
+    pub fn get_context() -> Option<String> {
+        Some("page".to_string())
+    }
+
+    /// Pages don't belong to a subject, so (unlike entities) their alias has no prefix.
+    fn get_alias(id: i32, instance: &Instance, title: Option<&str>) -> String {
+        format_alias(Some(instance), None, id, title)
+    }
+
+    /// Fetches the aliases of the given pages without loading the pages themselves. Pages that do
+    /// not exist are left out.
+    pub async fn fetch_aliases_via_transaction<'a, E>(
+        ids: &[i32],
+        executor: E,
+    ) -> Result<HashMap<i32, String>, UuidError>
+    where
+        E: Executor<'a>,
+    {
+        let pages: Vec<(i64, String, Option<String>)> = fetch_all_by_ids(
+            r#"
+                SELECT p.id, i.subdomain, r.title
+                    FROM page_repository p
+                    JOIN instance i ON i.id = p.instance_id
+                    LEFT JOIN page_revision r ON r.id = p.current_revision_id
+                    WHERE p.id IN ({})
+            "#,
+            ids,
+            executor,
+        )
+        .await?;
+        pages
+            .into_iter()
+            .map(|(id, subdomain, title)| {
+                let id = id as i32;
+                let instance: Instance =
+                    subdomain.parse().map_err(|_| UuidError::InvalidInstance)?;
+                Ok((id, Self::get_alias(id, &instance, title.as_deref())))
+            })
+            .collect()
+    }
+
+    /// Creates a new page together with its first revision, which becomes the current revision.
+    ///
+    /// The legacy event log has no event type for creating pages, so (like in the legacy page
+    /// flow) only adding and checking out the first revision are recorded.
+    pub async fn create<'a, E>(
+        payload: PageCreatePayload,
+        executor: E,
+    ) -> Result<Uuid, PageMutationError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        let license = sqlx::query!(
+            r#"
+                SELECT l.id
+                    FROM license l
+                    JOIN instance i ON i.id = l.instance_id
+                    WHERE l.id = ? AND i.subdomain = ?
+            "#,
+            payload.license_id,
+            payload.instance
+        )
+        .fetch_optional(&mut transaction)
+        .await?;
+        if license.is_none() {
+            return Err(PageMutationError::InvalidLicense);
+        }
+
+        sqlx::query!(
+            r#"
+                INSERT INTO uuid (trashed, discriminator)
+                    VALUES (0, 'page')
+            "#
+        )
+        .execute(&mut transaction)
+        .await?;
+        let value = sqlx::query!(r#"SELECT LAST_INSERT_ID() as id"#)
+            .fetch_one(&mut transaction)
+            .await?;
+        let page_id = value.id as i32;
+
+        sqlx::query!(
+            r#"
+                INSERT INTO page_repository (id, instance_id, license_id, current_revision_id)
+                    SELECT ?, i.id, ?, NULL
+                    FROM instance i
+                    WHERE i.subdomain = ?
+            "#,
+            page_id,
+            payload.license_id,
+            payload.instance
+        )
+        .execute(&mut transaction)
+        .await?;
+
+        let revision_id = Self::insert_revision(
+            page_id,
+            &payload.title,
+            &payload.content,
+            payload.user_id,
+            &mut transaction,
+        )
+        .await?;
+
+        CreateEntityRevisionEventPayload::new(
+            page_id,
+            revision_id,
+            payload.user_id,
+            payload.instance.clone(),
+        )
+        .save(&mut transaction)
+        .await?;
+
+        Self::set_current_revision(page_id, revision_id, &mut transaction).await?;
+        CheckoutRevisionEventPayload::new(
+            page_id,
+            revision_id,
+            "".to_string(),
+            payload.user_id,
+            payload.instance,
+        )
+        .save(&mut transaction)
+        .await?;
+
+        Subscription::auto_subscribe(
+            &[page_id],
+            payload.user_id,
+            payload.subscribe,
+            payload.send_email,
+            &mut transaction,
+        )
+        .await?;
+
+        let page = Page::fetch_via_transaction(page_id, &mut transaction).await?;
+
+        transaction.commit().await?;
+
+        Ok(page)
+    }
+
+    /// Adds a new revision to the page without checking it out.
+    pub async fn add_revision<'a, E>(
+        payload: PageAddRevisionPayload,
+        executor: E,
+    ) -> Result<Uuid, PageMutationError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        let instance = Self::fetch_instance(payload.page_id, &mut transaction)
+            .await?
+            .ok_or(PageMutationError::PageNotFound)?;
+
+        let revision_id = Self::insert_revision(
+            payload.page_id,
+            &payload.title,
+            &payload.content,
+            payload.user_id,
+            &mut transaction,
+        )
+        .await?;
+        CreateEntityRevisionEventPayload::new(
+            payload.page_id,
+            revision_id,
+            payload.user_id,
+            instance,
+        )
+        .save(&mut transaction)
+        .await?;
+
+        Subscription::auto_subscribe(
+            &[payload.page_id],
+            payload.user_id,
+            payload.subscribe,
+            payload.send_email,
+            &mut transaction,
+        )
+        .await?;
+
+        let revision = Uuid::fetch_via_transaction(revision_id, &mut transaction).await?;
+
+        transaction.commit().await?;
+
+        Ok(revision)
+    }
+
+    /// Makes the revision the current revision of its page.
+    pub async fn checkout_revision<'a, E>(
+        payload: PageCheckoutRevisionPayload,
+        executor: E,
+    ) -> Result<Uuid, PageMutationError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        let revision = sqlx::query!(
+            r#"
+                SELECT r.page_repository_id, p.current_revision_id
+                    FROM page_revision r
+                    JOIN page_repository p ON p.id = r.page_repository_id
+                    WHERE r.id = ?
+            "#,
+            payload.revision_id
+        )
+        .fetch_optional(&mut transaction)
+        .await?
+        .ok_or(PageMutationError::RevisionNotFound)?;
+        let page_id = revision.page_repository_id as i32;
+
+        if revision.current_revision_id == Some(payload.revision_id) {
+            return Err(PageMutationError::RevisionAlreadyCheckedOut);
+        }
+
+        let instance = Self::fetch_instance(page_id, &mut transaction)
+            .await?
+            .ok_or(PageMutationError::PageNotFound)?;
+
+        Self::set_current_revision(page_id, payload.revision_id, &mut transaction).await?;
+        CheckoutRevisionEventPayload::new(
+            page_id,
+            payload.revision_id,
+            payload.reason,
+            payload.user_id,
+            instance,
+        )
+        .save(&mut transaction)
+        .await?;
+
+        let page = Page::fetch_via_transaction(page_id, &mut transaction).await?;
+
+        transaction.commit().await?;
+
+        Ok(page)
+    }
+
+    async fn fetch_instance<'a, E>(
+        page_id: i32,
+        executor: E,
+    ) -> Result<Option<Instance>, PageMutationError>
+    where
+        E: Executor<'a>,
+    {
+        let page = sqlx::query!(
+            r#"
+                SELECT i.subdomain
+                    FROM page_repository p
+                    JOIN instance i ON i.id = p.instance_id
+                    WHERE p.id = ?
+            "#,
+            page_id
+        )
+        .fetch_optional(executor)
+        .await?;
+
+        match page {
+            Some(page) => Ok(Some(
+                page.subdomain
+                    .parse()
+                    .map_err(|_| PageMutationError::InvalidInstance)?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    async fn insert_revision<'a, E>(
+        page_id: i32,
+        title: &str,
+        content: &str,
+        user_id: i32,
+        executor: E,
+    ) -> Result<i32, PageMutationError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        sqlx::query!(
+            r#"
+                INSERT INTO uuid (trashed, discriminator)
+                    VALUES (0, 'pageRevision')
+            "#
+        )
+        .execute(&mut transaction)
+        .await?;
+        sqlx::query!(
+            r#"
+                INSERT INTO page_revision (id, author_id, page_repository_id, title, content, date)
+                    VALUES (LAST_INSERT_ID(), ?, ?, ?, ?, ?)
+            "#,
+            user_id,
+            page_id,
+            title,
+            content,
+            DateTime::now()
+        )
+        .execute(&mut transaction)
+        .await?;
+        let value = sqlx::query!(r#"SELECT LAST_INSERT_ID() as id"#)
+            .fetch_one(&mut transaction)
+            .await?;
+
+        transaction.commit().await?;
+
+        Ok(value.id as i32)
+    }
+
+    async fn set_current_revision<'a, E>(
+        page_id: i32,
+        revision_id: i32,
+        executor: E,
+    ) -> Result<(), PageMutationError>
+    where
+        E: Executor<'a>,
+    {
+        sqlx::query!(
+            r#"UPDATE page_repository SET current_revision_id = ? WHERE id = ?"#,
+            revision_id,
+            page_id
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Page, PageAddRevisionPayload, PageCheckoutRevisionPayload, PageCreatePayload,
+        PageMutationError,
+    };
+    use crate::create_database_pool;
+    use crate::instance::Instance;
+    use crate::uuid::{ConcreteUuid, Uuid};
+
+    async fn create_page<'a>(transaction: &mut sqlx::Transaction<'a, sqlx::MySql>) -> (i32, i32) {
+        let uuid = Page::create(
+            PageCreatePayload {
+                instance: Instance::De,
+                license_id: 1,
+                title: "Impressum".to_string(),
+                content: "Inhalt".to_string(),
+                user_id: 1,
+                subscribe: true,
+                send_email: false,
+            },
+            transaction,
+        )
+        .await
+        .unwrap();
+
+        match uuid.concrete_uuid {
+            ConcreteUuid::Page(page) => (uuid.id, page.current_revision_id.unwrap()),
+            _ => panic!("Page::create did not return a page"),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn create_page_checks_out_first_revision() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        let (page_id, revision_id) = create_page(&mut transaction).await;
+
+        let uuid = Uuid::fetch_via_transaction(page_id, &mut transaction)
+            .await
+            .unwrap();
+        if let ConcreteUuid::Page(page) = uuid.concrete_uuid {
+            assert_eq!(page.revision_ids, vec![revision_id]);
+            assert_eq!(page.license_id, 1);
+        } else {
+            panic!("UUID is not a page")
+        }
+    }
+
+    /// Returns the names of the events about the given revision, oldest first.
+    async fn fetch_revision_events<'a>(
+        revision_id: i32,
+        transaction: &mut sqlx::Transaction<'a, sqlx::MySql>,
+    ) -> Vec<String> {
+        sqlx::query!(
+            r#"
+                SELECT e.name
+                    FROM event_log l
+                    JOIN event e ON e.id = l.event_id
+                    WHERE l.uuid_id = ?
+                    ORDER BY l.id
+            "#,
+            revision_id
+        )
+        .fetch_all(transaction)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|event| event.name)
+        .collect()
+    }
+
+    #[actix_rt::test]
+    async fn create_page_records_revision_events_and_subscribes_author() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        let (page_id, revision_id) = create_page(&mut transaction).await;
+
+        assert_eq!(
+            fetch_revision_events(revision_id, &mut transaction).await,
+            vec![
+                "entity/revision/add".to_string(),
+                "entity/revision/checkout".to_string()
+            ]
+        );
+
+        let subscription = sqlx::query!(
+            r#"SELECT user_id FROM subscription WHERE uuid_id = ?"#,
+            page_id
+        )
+        .fetch_one(&mut transaction)
+        .await
+        .unwrap();
+        assert_eq!(subscription.user_id, 1);
+    }
+
+    #[actix_rt::test]
+    async fn create_page_fails_with_license_of_other_instance() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        let license = sqlx::query!(
+            r#"
+                SELECT l.id
+                    FROM license l
+                    JOIN instance i ON i.id = l.instance_id
+                    WHERE i.subdomain != 'de'
+                    LIMIT 1
+            "#
+        )
+        .fetch_one(&mut transaction)
+        .await
+        .unwrap();
+
+        let result = Page::create(
+            PageCreatePayload {
+                instance: Instance::De,
+                license_id: license.id as i32,
+                title: "Impressum".to_string(),
+                content: "Inhalt".to_string(),
+                user_id: 1,
+                subscribe: true,
+                send_email: false,
+            },
+            &mut transaction,
+        )
+        .await;
+
+        assert!(matches!(result, Err(PageMutationError::InvalidLicense)));
+    }
+
+    #[actix_rt::test]
+    async fn add_and_checkout_revision() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        let (page_id, first_revision_id) = create_page(&mut transaction).await;
+
+        let revision = Page::add_revision(
+            PageAddRevisionPayload {
+                page_id,
+                title: "Impressum".to_string(),
+                content: "Neuer Inhalt".to_string(),
+                user_id: 1,
+                subscribe: true,
+                send_email: false,
+            },
+            &mut transaction,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            fetch_revision_events(revision.id, &mut transaction).await,
+            vec!["entity/revision/add".to_string()]
+        );
+
+        let page = Uuid::fetch_via_transaction(page_id, &mut transaction)
+            .await
+            .unwrap();
+        if let ConcreteUuid::Page(page) = page.concrete_uuid {
+            assert_eq!(page.current_revision_id, Some(first_revision_id));
+        } else {
+            panic!("UUID is not a page")
+        }
+
+        let page = Page::checkout_revision(
+            PageCheckoutRevisionPayload {
+                revision_id: revision.id,
+                reason: "".to_string(),
+                user_id: 1,
+            },
+            &mut transaction,
+        )
+        .await
+        .unwrap();
+        if let ConcreteUuid::Page(page) = page.concrete_uuid {
+            assert_eq!(page.current_revision_id, Some(revision.id));
+        } else {
+            panic!("UUID is not a page")
+        }
+        assert_eq!(
+            fetch_revision_events(revision.id, &mut transaction).await,
+            vec![
+                "entity/revision/add".to_string(),
+                "entity/revision/checkout".to_string()
+            ]
+        );
+
+        let result = Page::checkout_revision(
+            PageCheckoutRevisionPayload {
+                revision_id: revision.id,
+                reason: "".to_string(),
+                user_id: 1,
+            },
+            &mut transaction,
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(PageMutationError::RevisionAlreadyCheckedOut)
+        ));
+    }
+}
@@ -1,11 +1,15 @@
+use std::collections::HashMap;
+
 use async_trait::async_trait;
 use serde::Serialize;
 use sqlx::MySqlPool;
 
+use super::page::Page;
 use super::{ConcreteUuid, Uuid, UuidError, UuidFetcher};
-use crate::database::Executor;
+use crate::database::{fetch_all_by_ids, Executor};
 use crate::datetime::DateTime;
 use crate::format_alias;
+use crate::instance::Instance;
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -31,9 +35,11 @@ impl UuidFetcher for PageRevision {
     {
         sqlx::query!(
             r#"
-                SELECT u.trashed, r.title, r.content, r.date, r.author_id, r.page_repository_id
+                SELECT u.trashed, i.subdomain, r.title, r.content, r.date, r.author_id, r.page_repository_id
                     FROM page_revision r
                     JOIN uuid u ON u.id = r.id
+                    JOIN page_repository p ON p.id = r.page_repository_id
+                    JOIN instance i ON i.id = p.instance_id
                     WHERE r.id = ?
             "#,
             id
@@ -44,12 +50,15 @@ impl UuidFetcher for PageRevision {
             sqlx::Error::RowNotFound => UuidError::NotFound,
             error => error.into(),
         })
-        .map(|revision| {
-            Uuid {
+        .and_then(|revision| {
+            let instance: Instance = revision
+                .subdomain
+                .parse()
+                .map_err(|_| UuidError::InvalidInstance)?;
+            Ok(Uuid {
                 id,
                 trashed: revision.trashed != 0,
-                // TODO:
-                alias: format_alias(None, id, Some(&revision.title)),
+                alias: PageRevision::get_alias(id, &instance, &revision.title),
                 concrete_uuid: ConcreteUuid::PageRevision(PageRevision {
                     __typename: "PageRevision".to_string(),
                     title: revision.title,
@@ -58,7 +67,49 @@ impl UuidFetcher for PageRevision {
                     author_id: revision.author_id as i32,
                     repository_id: revision.page_repository_id as i32,
                 }),
-            }
+            })
         })
     }
 }
+
+impl PageRevision {
+    pub fn get_context() -> Option<String> {
+        Page::get_context()
+    }
+
+    fn get_alias(id: i32, instance: &Instance, title: &str) -> String {
+        format_alias(Some(instance), None, id, Some(title))
+    }
+
+    /// Fetches the aliases of the given page revisions without loading the revisions themselves.
+    /// Revisions that do not exist are left out.
+    pub async fn fetch_aliases_via_transaction<'a, E>(
+        ids: &[i32],
+        executor: E,
+    ) -> Result<HashMap<i32, String>, UuidError>
+    where
+        E: Executor<'a>,
+    {
+        let revisions: Vec<(i64, String, String)> = fetch_all_by_ids(
+            r#"
+                SELECT r.id, i.subdomain, r.title
+                    FROM page_revision r
+                    JOIN page_repository p ON p.id = r.page_repository_id
+                    JOIN instance i ON i.id = p.instance_id
+                    WHERE r.id IN ({})
+            "#,
+            ids,
+            executor,
+        )
+        .await?;
+        revisions
+            .into_iter()
+            .map(|(id, subdomain, title)| {
+                let id = id as i32;
+                let instance: Instance =
+                    subdomain.parse().map_err(|_| UuidError::InvalidInstance)?;
+                Ok((id, Self::get_alias(id, &instance, &title)))
+            })
+            .collect()
+    }
+}
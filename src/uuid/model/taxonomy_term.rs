@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use async_trait::async_trait;
 use convert_case::{Case, Casing};
 use futures::join;
@@ -5,7 +7,7 @@ use serde::Serialize;
 use sqlx::MySqlPool;
 
 use super::{ConcreteUuid, Uuid, UuidError, UuidFetcher};
-use crate::database::Executor;
+use crate::database::{fetch_all_by_ids, Executor};
 use crate::format_alias;
 use crate::instance::Instance;
 
@@ -94,17 +96,18 @@ macro_rules! to_taxonomy_term {
             .map(|child| child.entity_id as i32)
             .collect();
         children_ids.extend(children.iter().map(|child| child.id as i32));
+        let instance: Instance = taxonomy_term
+            .subdomain
+            .parse()
+            .map_err(|_| UuidError::InvalidInstance)?;
         Ok(Uuid {
             id: $id,
             trashed: taxonomy_term.trashed != 0,
-            alias: format_alias(subject.as_deref(), $id, Some(&taxonomy_term.name)),
+            alias: TaxonomyTerm::get_alias($id, &instance, subject.as_deref(), &taxonomy_term.name),
             concrete_uuid: ConcreteUuid::TaxonomyTerm(TaxonomyTerm {
                 __typename: "TaxonomyTerm".to_string(),
                 term_type: TaxonomyTerm::normalize_type(taxonomy_term.term_type.as_str()),
-                instance: taxonomy_term
-                    .subdomain
-                    .parse()
-                    .map_err(|_| UuidError::InvalidInstance)?,
+                instance,
                 name: taxonomy_term.name,
                 description: taxonomy_term.description,
                 weight: taxonomy_term.weight.unwrap_or(0),
@@ -147,6 +150,54 @@ impl UuidFetcher for TaxonomyTerm {
 }
 
 impl TaxonomyTerm {
+    fn get_alias(id: i32, instance: &Instance, subject: Option<&str>, name: &str) -> String {
+        format_alias(Some(instance), subject, id, Some(name))
+    }
+
+    /// Fetches the aliases of the given taxonomy terms without loading the taxonomy terms
+    /// themselves. Taxonomy terms that do not exist are left out.
+    pub async fn fetch_aliases_via_transaction<'a, E>(
+        ids: &[i32],
+        executor: E,
+    ) -> Result<HashMap<i32, String>, UuidError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        let taxonomy_terms: Vec<(i64, String, String)> = fetch_all_by_ids(
+            r#"
+                SELECT term_taxonomy.id, term.name, instance.subdomain
+                    FROM term_taxonomy
+                    JOIN term ON term.id = term_taxonomy.term_id
+                    JOIN taxonomy ON taxonomy.id = term_taxonomy.taxonomy_id
+                    JOIN instance ON instance.id = taxonomy.instance_id
+                    WHERE term_taxonomy.id IN ({})
+            "#,
+            ids,
+            &mut transaction,
+        )
+        .await?;
+
+        let mut subjects =
+            Self::fetch_canonical_subjects_via_transaction(ids, &mut transaction).await?;
+
+        let mut aliases = HashMap::new();
+        for (id, name, subdomain) in taxonomy_terms {
+            let id = id as i32;
+            let instance: Instance = subdomain.parse().map_err(|_| UuidError::InvalidInstance)?;
+            let subject = subjects.remove(&id).flatten();
+            aliases.insert(
+                id,
+                Self::get_alias(id, &instance, subject.as_deref(), &name),
+            );
+        }
+
+        transaction.commit().await?;
+
+        Ok(aliases)
+    }
+
     pub async fn fetch_canonical_subject<'a, E>(
         id: i32,
         executor: E,
@@ -215,6 +266,128 @@ impl TaxonomyTerm {
         }
     }
 
+    /// Like [`TaxonomyTerm::fetch_canonical_subject`] for many taxonomy terms, with one query per
+    /// level of the taxonomy instead of one query per taxonomy term.
+    pub async fn fetch_canonical_subjects_via_transaction<'a, E>(
+        ids: &[i32],
+        executor: E,
+    ) -> Result<HashMap<i32, Option<String>>, sqlx::Error>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        // Fetch the parents level by level, up to the root of the deepest supported taxonomy term
+        // (see the limit of 20 levels in `fetch_canonical_subject`).
+        let mut parents: HashMap<i32, Option<i32>> = HashMap::new();
+        let mut current_ids: Vec<i32> = ids.to_vec();
+        for _ in 0..21 {
+            current_ids.sort_unstable();
+            current_ids.dedup();
+            current_ids.retain(|id| !parents.contains_key(id));
+            if current_ids.is_empty() {
+                break;
+            }
+            let terms: Vec<(i64, Option<i64>)> = fetch_all_by_ids(
+                "SELECT id, parent_id FROM term_taxonomy WHERE id IN ({})",
+                &current_ids,
+                &mut transaction,
+            )
+            .await?;
+            current_ids = terms
+                .into_iter()
+                .filter_map(|(id, parent_id)| {
+                    let parent_id = parent_id.map(|parent_id| parent_id as i32);
+                    parents.insert(id as i32, parent_id);
+                    parent_id
+                })
+                .collect();
+        }
+
+        // The subject is the ancestor (or the taxonomy term itself) right below the root.
+        let subject_ids: HashMap<i32, i32> = ids
+            .iter()
+            .filter_map(|id| {
+                let mut current_id = *id;
+                for _ in 0..20 {
+                    let parent_id = (*parents.get(&current_id)?)?;
+                    if parents.get(&parent_id)?.is_none() {
+                        return Some((*id, current_id));
+                    }
+                    current_id = parent_id;
+                }
+                None
+            })
+            .collect();
+
+        let subject_term_ids: Vec<i32> = subject_ids.values().copied().collect();
+        let names: Vec<(i64, String)> = fetch_all_by_ids(
+            r#"
+                SELECT tt.id, t.name
+                    FROM term_taxonomy tt
+                    JOIN term t ON t.id = tt.term_id
+                    WHERE tt.id IN ({})
+            "#,
+            &subject_term_ids,
+            &mut transaction,
+        )
+        .await?;
+        let names: HashMap<i32, String> = names
+            .into_iter()
+            .map(|(id, name)| (id as i32, name))
+            .collect();
+
+        transaction.commit().await?;
+
+        Ok(ids
+            .iter()
+            .map(|id| {
+                let subject = subject_ids
+                    .get(id)
+                    .and_then(|subject_id| names.get(subject_id))
+                    .cloned();
+                (*id, subject)
+            })
+            .collect())
+    }
+
+    /// Fetches the ids of all ancestors of the taxonomy term, starting with its parent.
+    pub async fn fetch_ancestors_via_transaction<'a, E>(
+        id: i32,
+        executor: E,
+    ) -> Result<Vec<i32>, sqlx::Error>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        let mut ancestors: Vec<i32> = Vec::new();
+        let mut current_id = id;
+        loop {
+            let parent_id = sqlx::query!(
+                r#"SELECT parent_id FROM term_taxonomy WHERE id = ?"#,
+                current_id
+            )
+            .fetch_optional(&mut transaction)
+            .await?
+            .and_then(|term| term.parent_id)
+            .map(|parent_id| parent_id as i32);
+
+            match parent_id {
+                // Guard against cycles in the tree, so that we don't loop forever.
+                Some(parent_id) if parent_id != id && !ancestors.contains(&parent_id) => {
+                    ancestors.push(parent_id);
+                    current_id = parent_id;
+                }
+                _ => break,
+            }
+        }
+
+        transaction.commit().await?;
+
+        Ok(ancestors)
+    }
+
     fn normalize_type(typename: &str) -> String {
         typename.to_case(Case::Camel)
     }
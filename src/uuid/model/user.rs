@@ -1,11 +1,21 @@
+use std::collections::HashMap;
+
 use async_trait::async_trait;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use sqlx::MySqlPool;
+use thiserror::Error;
 
 use super::{ConcreteUuid, Uuid, UuidError, UuidFetcher};
-use crate::database::Executor;
+use crate::database::{fetch_all_by_ids, Executor};
 use crate::datetime::DateTime;
 use crate::format_alias;
+use crate::user::{User as ActiveUsers, UserError};
+
+/// The `user_field` in which the id of the user at the identity provider is stored.
+const IDENTITY_PROVIDER_ID_FIELD: &str = "identity_provider_id";
+
+/// Contributions of anonymized users are reassigned to the user with this username.
+const DELETED_USERNAME: &str = "Deleted";
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -16,6 +26,26 @@ pub struct User {
     pub date: DateTime,
     pub last_login: Option<DateTime>,
     pub description: Option<String>,
+    pub logins: i32,
+    pub roles: Vec<String>,
+    /// Only meant for privileged callers, see [`User::strip_private_fields`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// Only set on request, see [`User::fetch_activity_via_transaction`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_active_author: Option<bool>,
+    /// Only set on request, see [`User::fetch_activity_via_transaction`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_active_reviewer: Option<bool>,
+}
+
+impl From<UserError> for UuidError {
+    fn from(error: UserError) -> Self {
+        match error {
+            UserError::DatabaseError { inner } => inner.into(),
+            UserError::InvalidInstance => UuidError::InvalidInstance,
+        }
+    }
 }
 
 #[async_trait]
@@ -28,31 +58,53 @@ impl UuidFetcher for User {
     where
         E: Executor<'a>,
     {
-        sqlx::query!(
+        let mut transaction = executor.begin().await?;
+
+        let user = sqlx::query!(
             r#"
-                SELECT trashed, username, date, last_login, description
+                SELECT trashed, username, email, logins, date, last_login, description
                     FROM user
                     JOIN uuid ON user.id = uuid.id
                     WHERE user.id = ?
             "#,
             id
         )
-        .fetch_one(executor)
+        .fetch_one(&mut transaction)
         .await
         .map_err(|error| match error {
             sqlx::Error::RowNotFound => UuidError::NotFound,
             error => error.into(),
-        })
-        .map(|user| Uuid {
+        })?;
+        let roles = sqlx::query!(
+            r#"
+                SELECT r.name
+                    FROM role r
+                    JOIN role_user ru ON r.id = ru.role_id
+                    WHERE ru.user_id = ?
+                    ORDER BY r.name
+            "#,
+            id
+        )
+        .fetch_all(&mut transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(Uuid {
             id,
             trashed: user.trashed != 0,
-            alias: format_alias(Self::get_context().as_deref(), id, Some(&user.username)),
+            alias: Self::get_alias(id, &user.username),
             concrete_uuid: ConcreteUuid::User(User {
                 __typename: "User".to_string(),
                 username: user.username,
                 date: user.date.into(),
                 last_login: user.last_login.map(|date| date.into()),
                 description: user.description,
+                logins: user.logins,
+                roles: roles.into_iter().map(|role| role.name).collect(),
+                email: Some(user.email),
+                is_active_author: None,
+                is_active_reviewer: None,
             }),
         })
     }
@@ -62,4 +114,1161 @@ impl User {
     pub fn get_context() -> Option<String> {
         Some("user".to_string())
     }
+
+    fn get_alias(id: i32, username: &str) -> String {
+        // Users don't belong to an instance in the legacy schema.
+        format_alias(None, Self::get_context().as_deref(), id, Some(username))
+    }
+
+    /// Fetches the aliases of the given users without loading the users themselves. Users that do
+    /// not exist are left out.
+    pub async fn fetch_aliases_via_transaction<'a, E>(
+        ids: &[i32],
+        executor: E,
+    ) -> Result<HashMap<i32, String>, UuidError>
+    where
+        E: Executor<'a>,
+    {
+        let users: Vec<(i64, String)> = fetch_all_by_ids(
+            r#"SELECT id, username FROM user WHERE id IN ({})"#,
+            ids,
+            executor,
+        )
+        .await?;
+        Ok(users
+            .into_iter()
+            .map(|(id, username)| (id as i32, Self::get_alias(id as i32, &username)))
+            .collect())
+    }
+
+    /// Returns the id of the user with the given username (if any).
+    pub async fn fetch_id_by_username<'a, E>(
+        username: &str,
+        executor: E,
+    ) -> Result<Option<i32>, sqlx::Error>
+    where
+        E: Executor<'a>,
+    {
+        let user = sqlx::query!(r#"SELECT id FROM user WHERE username = ?"#, username)
+            .fetch_optional(executor)
+            .await?;
+        Ok(user.map(|user| user.id as i32))
+    }
+
+    pub async fn exists_username<'a, E>(username: &str, executor: E) -> Result<bool, sqlx::Error>
+    where
+        E: Executor<'a>,
+    {
+        Ok(Self::fetch_id_by_username(username, executor)
+            .await?
+            .is_some())
+    }
+
+    pub async fn fetch_by_username(username: &str, pool: &MySqlPool) -> Result<Uuid, UuidError> {
+        Self::fetch_by_username_via_transaction(username, pool).await
+    }
+
+    pub async fn fetch_by_username_via_transaction<'a, E>(
+        username: &str,
+        executor: E,
+    ) -> Result<Uuid, UuidError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        let id = Self::fetch_id_by_username(username, &mut transaction)
+            .await?
+            .ok_or(UuidError::NotFound)?;
+        let user = Self::fetch_via_transaction(id, &mut transaction).await?;
+
+        transaction.commit().await?;
+
+        Ok(user)
+    }
+
+    /// Sets whether the fetched user is an active author and reviewer (with the default criteria,
+    /// see [`ActiveUsers::fetch_active_authors`]). Since this needs to scan the event log, it is
+    /// only done if a caller asks for it.
+    ///
+    /// There is no active donor status: donations are not recorded in this database, so callers
+    /// need to look them up where they are tracked.
+    pub async fn fetch_activity_via_transaction<'a, E>(
+        uuid: &mut Uuid,
+        executor: E,
+    ) -> Result<(), UuidError>
+    where
+        E: Executor<'a>,
+    {
+        if let ConcreteUuid::User(user) = &mut uuid.concrete_uuid {
+            let mut transaction = executor.begin().await?;
+
+            user.is_active_author = Some(
+                ActiveUsers::is_active_author_via_transaction(uuid.id, &mut transaction).await?,
+            );
+            user.is_active_reviewer = Some(
+                ActiveUsers::is_active_reviewer_via_transaction(uuid.id, &mut transaction).await?,
+            );
+
+            transaction.commit().await?;
+        }
+        Ok(())
+    }
+
+    /// Removes the fields that only privileged callers may see from a fetched user.
+    pub fn strip_private_fields(uuid: &mut Uuid) {
+        if let ConcreteUuid::User(user) = &mut uuid.concrete_uuid {
+            user.email = None;
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Users {
+    pub users: Vec<Uuid>,
+}
+
+#[derive(Debug, Default)]
+pub struct UsersFilter {
+    pub username_prefix: Option<String>,
+    pub role: Option<String>,
+    pub registered_after: Option<DateTime>,
+}
+
+impl Users {
+    pub async fn fetch(
+        filter: UsersFilter,
+        first: i32,
+        after: Option<i32>,
+        pool: &MySqlPool,
+    ) -> Result<Self, UuidError> {
+        Self::fetch_via_transaction(filter, first, after, pool).await
+    }
+
+    /// Fetches the first `first` users matching the filter whose id is greater than `after`,
+    /// ordered by id. Private fields are left out (see [`User::strip_private_fields`]).
+    pub async fn fetch_via_transaction<'a, E>(
+        filter: UsersFilter,
+        first: i32,
+        after: Option<i32>,
+        executor: E,
+    ) -> Result<Self, UuidError>
+    where
+        E: Executor<'a>,
+    {
+        // `%` and `_` in the prefix need to be matched literally.
+        let username_pattern = filter.username_prefix.map(|prefix| {
+            format!(
+                "{}%",
+                prefix
+                    .replace('\\', "\\\\")
+                    .replace('%', "\\%")
+                    .replace('_', "\\_")
+            )
+        });
+        let users = sqlx::query!(
+            r#"
+                SELECT u.id, uuid.trashed, u.username, u.logins, u.date, u.last_login, u.description,
+                        GROUP_CONCAT(r.name ORDER BY r.name SEPARATOR '\n') AS roles
+                    FROM user u
+                    JOIN uuid ON uuid.id = u.id
+                    LEFT JOIN role_user ru ON ru.user_id = u.id
+                    LEFT JOIN role r ON r.id = ru.role_id
+                    WHERE u.id > ?
+                        AND (? IS NULL OR u.username LIKE ?)
+                        AND (? IS NULL OR u.date > ?)
+                        AND (? IS NULL OR EXISTS (
+                            SELECT ru.user_id
+                                FROM role_user ru
+                                JOIN role r ON r.id = ru.role_id
+                                WHERE ru.user_id = u.id AND r.name = ?
+                        ))
+                    GROUP BY u.id
+                    ORDER BY u.id
+                    LIMIT ?
+            "#,
+            after.unwrap_or(0),
+            username_pattern,
+            username_pattern,
+            filter.registered_after,
+            filter.registered_after,
+            filter.role,
+            filter.role,
+            first
+        )
+        .fetch_all(executor)
+        .await?;
+
+        let users = users
+            .into_iter()
+            .map(|user| {
+                let id = user.id as i32;
+                Uuid {
+                    id,
+                    trashed: user.trashed != 0,
+                    alias: User::get_alias(id, &user.username),
+                    concrete_uuid: ConcreteUuid::User(User {
+                        __typename: "User".to_string(),
+                        username: user.username,
+                        date: user.date.into(),
+                        last_login: user.last_login.map(|date| date.into()),
+                        description: user.description,
+                        logins: user.logins,
+                        roles: user
+                            .roles
+                            .map(|roles| roles.split('\n').map(String::from).collect())
+                            .unwrap_or_default(),
+                        email: None,
+                        is_active_author: None,
+                        is_active_reviewer: None,
+                    }),
+                }
+            })
+            .collect();
+
+        Ok(Users { users })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserCreatePayload {
+    pub username: String,
+    pub email: String,
+    pub description: Option<String>,
+    pub identity_provider_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserUpdateDescriptionPayload {
+    pub user_id: i32,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserSetLastLoginPayload {
+    pub user_id: i32,
+    /// Defaults to the current time.
+    pub last_login: Option<DateTime>,
+}
+
+#[derive(Error, Debug)]
+pub enum UserMutationError {
+    #[error("User cannot be changed because of a database error: {inner:?}.")]
+    DatabaseError { inner: sqlx::Error },
+    #[error("User cannot be changed because of an uuid error: {inner:?}.")]
+    UuidError { inner: UuidError },
+    #[error("User cannot be created because the username is invalid.")]
+    InvalidUsername,
+    #[error("User cannot be created because the username is already taken.")]
+    UsernameTaken,
+    #[error("User cannot be created because the email is already taken.")]
+    EmailTaken,
+    #[error("User cannot be created because the identity provider id is already taken.")]
+    IdentityProviderIdTaken,
+    #[error("User cannot be changed because it does not exist.")]
+    NotFound,
+}
+
+impl From<sqlx::Error> for UserMutationError {
+    fn from(inner: sqlx::Error) -> Self {
+        UserMutationError::DatabaseError { inner }
+    }
+}
+
+impl From<UuidError> for UserMutationError {
+    fn from(error: UuidError) -> Self {
+        match error {
+            UuidError::DatabaseError { inner } => inner.into(),
+            inner => UserMutationError::UuidError { inner },
+        }
+    }
+}
+
+impl User {
+    /// Creates a new user. Passwords are handled by the identity provider, so the legacy password
+    /// column stays empty.
+    pub async fn create<'a, E>(
+        payload: UserCreatePayload,
+        executor: E,
+    ) -> Result<Uuid, UserMutationError>
+    where
+        E: Executor<'a>,
+    {
+        // Usernames end up in the alias `user/profile/{username}`.
+        if payload.username.trim().is_empty() || payload.username.contains('/') {
+            return Err(UserMutationError::InvalidUsername);
+        }
+
+        let mut transaction = executor.begin().await?;
+
+        if Self::fetch_id_by_username(&payload.username, &mut transaction)
+            .await?
+            .is_some()
+        {
+            return Err(UserMutationError::UsernameTaken);
+        }
+        let user = sqlx::query!(r#"SELECT id FROM user WHERE email = ?"#, payload.email)
+            .fetch_optional(&mut transaction)
+            .await?;
+        if user.is_some() {
+            return Err(UserMutationError::EmailTaken);
+        }
+        if let Some(identity_provider_id) = &payload.identity_provider_id {
+            if Self::fetch_id_by_identity_provider_id(identity_provider_id, &mut transaction)
+                .await?
+                .is_some()
+            {
+                return Err(UserMutationError::IdentityProviderIdTaken);
+            }
+        }
+
+        sqlx::query!(
+            r#"
+                INSERT INTO uuid (trashed, discriminator)
+                    VALUES (0, 'user')
+            "#
+        )
+        .execute(&mut transaction)
+        .await?;
+        let value = sqlx::query!(r#"SELECT LAST_INSERT_ID() as id"#)
+            .fetch_one(&mut transaction)
+            .await?;
+        let user_id = value.id as i32;
+
+        sqlx::query!(
+            r#"
+                INSERT INTO user (id, email, username, password, logins, date, token, description)
+                    VALUES (?, ?, ?, '', 0, ?, MD5(UUID()), ?)
+            "#,
+            user_id,
+            payload.email,
+            payload.username,
+            DateTime::now(),
+            payload.description
+        )
+        .execute(&mut transaction)
+        .await?;
+
+        if let Some(identity_provider_id) = &payload.identity_provider_id {
+            sqlx::query!(
+                r#"
+                    INSERT INTO user_field (user_id, field, value)
+                        VALUES (?, ?, ?)
+                "#,
+                user_id,
+                IDENTITY_PROVIDER_ID_FIELD,
+                identity_provider_id
+            )
+            .execute(&mut transaction)
+            .await?;
+        }
+
+        let user = Self::fetch_via_transaction(user_id, &mut transaction).await?;
+
+        transaction.commit().await?;
+
+        Ok(user)
+    }
+
+    /// Returns the id of the user with the given id at the identity provider (if any).
+    pub async fn fetch_id_by_identity_provider_id<'a, E>(
+        identity_provider_id: &str,
+        executor: E,
+    ) -> Result<Option<i32>, sqlx::Error>
+    where
+        E: Executor<'a>,
+    {
+        let field = sqlx::query!(
+            r#"SELECT user_id FROM user_field WHERE field = ? AND value = ?"#,
+            IDENTITY_PROVIDER_ID_FIELD,
+            identity_provider_id
+        )
+        .fetch_optional(executor)
+        .await?;
+        Ok(field.map(|field| field.user_id as i32))
+    }
+
+    pub async fn update_description<'a, E>(
+        payload: UserUpdateDescriptionPayload,
+        executor: E,
+    ) -> Result<Uuid, UserMutationError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        Self::ensure_exists(payload.user_id, &mut transaction).await?;
+        sqlx::query!(
+            r#"UPDATE user SET description = ? WHERE id = ?"#,
+            payload.description,
+            payload.user_id
+        )
+        .execute(&mut transaction)
+        .await?;
+
+        let user = Self::fetch_via_transaction(payload.user_id, &mut transaction).await?;
+
+        transaction.commit().await?;
+
+        Ok(user)
+    }
+
+    /// Stores a successful login of the user, which also increases the number of logins.
+    pub async fn set_last_login<'a, E>(
+        payload: UserSetLastLoginPayload,
+        executor: E,
+    ) -> Result<Uuid, UserMutationError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        Self::ensure_exists(payload.user_id, &mut transaction).await?;
+        sqlx::query!(
+            r#"UPDATE user SET last_login = ?, logins = logins + 1 WHERE id = ?"#,
+            payload.last_login.unwrap_or_else(DateTime::now),
+            payload.user_id
+        )
+        .execute(&mut transaction)
+        .await?;
+
+        let user = Self::fetch_via_transaction(payload.user_id, &mut transaction).await?;
+
+        transaction.commit().await?;
+
+        Ok(user)
+    }
+
+    async fn ensure_exists<'a, E>(user_id: i32, executor: E) -> Result<(), UserMutationError>
+    where
+        E: Executor<'a>,
+    {
+        sqlx::query!(r#"SELECT id FROM user WHERE id = ?"#, user_id)
+            .fetch_optional(executor)
+            .await?
+            .ok_or(UserMutationError::NotFound)?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UserDeleteMode {
+    /// Removes the user completely. Only possible for users without any contributions.
+    HardDelete,
+    /// Reassigns the contributions of the user to the placeholder user and wipes the personal data
+    /// while keeping the (trashed) user row for referential integrity.
+    Anonymize,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserDeletePayload {
+    pub user_id: i32,
+    pub mode: UserDeleteMode,
+    /// Only reports the affected rows without changing anything.
+    pub dry_run: bool,
+}
+
+/// The number of rows affected by a user deletion (or that would be affected in a dry run).
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserDeleteAffectedRows {
+    pub event_logs: u64,
+    pub entity_revisions: u64,
+    pub comments: u64,
+    pub page_revisions: u64,
+    pub blog_posts: u64,
+    pub subscriptions: u64,
+    pub notifications: u64,
+    pub user_fields: u64,
+    pub roles: u64,
+    pub users: u64,
+    pub uuids: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserDeleteReport {
+    pub user_id: i32,
+    pub mode: UserDeleteMode,
+    pub dry_run: bool,
+    pub affected_rows: UserDeleteAffectedRows,
+}
+
+#[derive(Error, Debug)]
+pub enum UserDeleteError {
+    #[error("User cannot be deleted because of a database error: {inner:?}.")]
+    DatabaseError { inner: sqlx::Error },
+    #[error("User cannot be deleted because it does not exist.")]
+    NotFound,
+    #[error("User cannot be hard-deleted because it has contributions.")]
+    HasContributions,
+    #[error("User cannot be anonymized because the placeholder user does not exist.")]
+    PlaceholderNotFound,
+    #[error("User cannot be deleted because it is the placeholder user.")]
+    IsPlaceholder,
+}
+
+impl From<sqlx::Error> for UserDeleteError {
+    fn from(inner: sqlx::Error) -> Self {
+        UserDeleteError::DatabaseError { inner }
+    }
+}
+
+impl User {
+    /// Deletes or anonymizes the user in a single transaction. In a dry run, the transaction is
+    /// rolled back so that the report contains the exact number of rows that would be affected.
+    pub async fn delete<'a, E>(
+        payload: UserDeletePayload,
+        executor: E,
+    ) -> Result<UserDeleteReport, UserDeleteError>
+    where
+        E: Executor<'a>,
+    {
+        let mut transaction = executor.begin().await?;
+
+        let user_id = payload.user_id;
+        sqlx::query!(r#"SELECT id FROM user WHERE id = ?"#, user_id)
+            .fetch_optional(&mut transaction)
+            .await?
+            .ok_or(UserDeleteError::NotFound)?;
+        let placeholder_id = Self::fetch_id_by_username(DELETED_USERNAME, &mut transaction).await?;
+        if placeholder_id == Some(user_id) {
+            return Err(UserDeleteError::IsPlaceholder);
+        }
+
+        let mut affected_rows = UserDeleteAffectedRows::default();
+
+        match payload.mode {
+            UserDeleteMode::HardDelete => {
+                // Events and threads about the user (e.g. on their profile) count as well.
+                let contributions = sqlx::query!(
+                    r#"
+                        SELECT
+                            (SELECT COUNT(*) FROM event_log WHERE actor_id = ? OR uuid_id = ?) +
+                            (SELECT COUNT(*) FROM entity_revision WHERE author_id = ?) +
+                            (SELECT COUNT(*) FROM comment WHERE author_id = ? OR uuid_id = ?) +
+                            (SELECT COUNT(*) FROM page_revision WHERE author_id = ?) +
+                            (SELECT COUNT(*) FROM blog_post WHERE author_id = ?) AS count
+                    "#,
+                    user_id,
+                    user_id,
+                    user_id,
+                    user_id,
+                    user_id,
+                    user_id,
+                    user_id
+                )
+                .fetch_one(&mut transaction)
+                .await?;
+                if contributions.count.unwrap_or(0) > 0 {
+                    return Err(UserDeleteError::HasContributions);
+                }
+            }
+            UserDeleteMode::Anonymize => {
+                let placeholder_id = placeholder_id.ok_or(UserDeleteError::PlaceholderNotFound)?;
+
+                affected_rows.event_logs = sqlx::query!(
+                    r#"UPDATE event_log SET actor_id = ? WHERE actor_id = ?"#,
+                    placeholder_id,
+                    user_id
+                )
+                .execute(&mut transaction)
+                .await?
+                .rows_affected();
+                affected_rows.entity_revisions = sqlx::query!(
+                    r#"UPDATE entity_revision SET author_id = ? WHERE author_id = ?"#,
+                    placeholder_id,
+                    user_id
+                )
+                .execute(&mut transaction)
+                .await?
+                .rows_affected();
+                affected_rows.comments = sqlx::query!(
+                    r#"UPDATE comment SET author_id = ? WHERE author_id = ?"#,
+                    placeholder_id,
+                    user_id
+                )
+                .execute(&mut transaction)
+                .await?
+                .rows_affected();
+                affected_rows.page_revisions = sqlx::query!(
+                    r#"UPDATE page_revision SET author_id = ? WHERE author_id = ?"#,
+                    placeholder_id,
+                    user_id
+                )
+                .execute(&mut transaction)
+                .await?
+                .rows_affected();
+                affected_rows.blog_posts = sqlx::query!(
+                    r#"UPDATE blog_post SET author_id = ? WHERE author_id = ?"#,
+                    placeholder_id,
+                    user_id
+                )
+                .execute(&mut transaction)
+                .await?
+                .rows_affected();
+            }
+        }
+
+        affected_rows.subscriptions = sqlx::query!(
+            r#"DELETE FROM subscription WHERE user_id = ? OR uuid_id = ?"#,
+            user_id,
+            user_id
+        )
+        .execute(&mut transaction)
+        .await?
+        .rows_affected();
+        sqlx::query!(
+            r#"
+                DELETE ne
+                    FROM notification_event ne
+                    JOIN notification n ON n.id = ne.notification_id
+                    WHERE n.user_id = ?
+            "#,
+            user_id
+        )
+        .execute(&mut transaction)
+        .await?;
+        affected_rows.notifications =
+            sqlx::query!(r#"DELETE FROM notification WHERE user_id = ?"#, user_id)
+                .execute(&mut transaction)
+                .await?
+                .rows_affected();
+        affected_rows.user_fields =
+            sqlx::query!(r#"DELETE FROM user_field WHERE user_id = ?"#, user_id)
+                .execute(&mut transaction)
+                .await?
+                .rows_affected();
+        affected_rows.roles = sqlx::query!(r#"DELETE FROM role_user WHERE user_id = ?"#, user_id)
+            .execute(&mut transaction)
+            .await?
+            .rows_affected();
+
+        match payload.mode {
+            UserDeleteMode::HardDelete => {
+                affected_rows.users = sqlx::query!(r#"DELETE FROM user WHERE id = ?"#, user_id)
+                    .execute(&mut transaction)
+                    .await?
+                    .rows_affected();
+                affected_rows.uuids = sqlx::query!(r#"DELETE FROM uuid WHERE id = ?"#, user_id)
+                    .execute(&mut transaction)
+                    .await?
+                    .rows_affected();
+            }
+            UserDeleteMode::Anonymize => {
+                // Username and email need to stay unique, so they are derived from the id.
+                affected_rows.users = sqlx::query!(
+                    r#"
+                        UPDATE user
+                            SET username = ?, email = ?, password = '', token = MD5(UUID()),
+                                description = NULL, last_login = NULL
+                            WHERE id = ?
+                    "#,
+                    format!("deleted-{}", user_id),
+                    format!("deleted-{}@deleted.invalid", user_id),
+                    user_id
+                )
+                .execute(&mut transaction)
+                .await?
+                .rows_affected();
+                affected_rows.uuids =
+                    sqlx::query!(r#"UPDATE uuid SET trashed = 1 WHERE id = ?"#, user_id)
+                        .execute(&mut transaction)
+                        .await?
+                        .rows_affected();
+            }
+        }
+
+        if payload.dry_run {
+            transaction.rollback().await?;
+        } else {
+            transaction.commit().await?;
+        }
+
+        Ok(UserDeleteReport {
+            user_id,
+            mode: payload.mode,
+            dry_run: payload.dry_run,
+            affected_rows,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        User, UserCreatePayload, UserDeleteError, UserDeleteMode, UserDeletePayload,
+        UserMutationError, UserSetLastLoginPayload, UserUpdateDescriptionPayload, Users,
+        UsersFilter, DELETED_USERNAME,
+    };
+    use crate::create_database_pool;
+    use crate::datetime::DateTime;
+    use crate::uuid::{ConcreteUuid, UuidError, UuidFetcher};
+
+    #[actix_rt::test]
+    async fn fetch_by_username() {
+        let pool = create_database_pool().await.unwrap();
+
+        let mut uuid = User::fetch_by_username("admin", &pool).await.unwrap();
+
+        assert_eq!(uuid.id, 1);
+        if let ConcreteUuid::User(user) = &uuid.concrete_uuid {
+            assert_eq!(user.username, "admin");
+            assert!(user.roles.iter().any(|role| role == "sysadmin"));
+            assert!(user.email.is_some());
+            assert!(user.is_active_author.is_none());
+        } else {
+            panic!("UUID is not a user")
+        }
+
+        User::strip_private_fields(&mut uuid);
+        if let ConcreteUuid::User(user) = &uuid.concrete_uuid {
+            assert!(user.email.is_none());
+        }
+    }
+
+    #[actix_rt::test]
+    async fn fetch_activity() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        let mut uuid = User::fetch_via_transaction(1, &mut transaction)
+            .await
+            .unwrap();
+        User::fetch_activity_via_transaction(&mut uuid, &mut transaction)
+            .await
+            .unwrap();
+
+        if let ConcreteUuid::User(user) = &uuid.concrete_uuid {
+            assert!(user.is_active_author.is_some());
+            assert!(user.is_active_reviewer.is_some());
+        } else {
+            panic!("UUID is not a user")
+        }
+    }
+
+    #[actix_rt::test]
+    async fn fetch_by_unknown_username() {
+        let pool = create_database_pool().await.unwrap();
+
+        let result = User::fetch_by_username("does-not-exist", &pool).await;
+
+        assert!(matches!(result, Err(UuidError::NotFound)));
+    }
+
+    fn create_payload() -> UserCreatePayload {
+        UserCreatePayload {
+            username: "new-user".to_string(),
+            email: "new-user@example.org".to_string(),
+            description: None,
+            identity_provider_id: Some("d8f1c4a0-identity".to_string()),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn create_user() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        let uuid = User::create(create_payload(), &mut transaction)
+            .await
+            .unwrap();
+
+        if let ConcreteUuid::User(user) = &uuid.concrete_uuid {
+            assert_eq!(user.username, "new-user");
+            assert_eq!(user.email, Some("new-user@example.org".to_string()));
+            assert_eq!(user.logins, 0);
+            assert!(user.last_login.is_none());
+        } else {
+            panic!("UUID is not a user")
+        }
+        assert_eq!(
+            User::fetch_id_by_identity_provider_id("d8f1c4a0-identity", &mut transaction)
+                .await
+                .unwrap(),
+            Some(uuid.id)
+        );
+    }
+
+    #[actix_rt::test]
+    async fn create_user_fails_with_taken_username() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        let result = User::create(
+            UserCreatePayload {
+                username: "admin".to_string(),
+                ..create_payload()
+            },
+            &mut transaction,
+        )
+        .await;
+
+        assert!(matches!(result, Err(UserMutationError::UsernameTaken)));
+    }
+
+    #[actix_rt::test]
+    async fn create_user_fails_with_taken_email() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        User::create(create_payload(), &mut transaction)
+            .await
+            .unwrap();
+        let result = User::create(
+            UserCreatePayload {
+                username: "other-user".to_string(),
+                identity_provider_id: None,
+                ..create_payload()
+            },
+            &mut transaction,
+        )
+        .await;
+
+        assert!(matches!(result, Err(UserMutationError::EmailTaken)));
+    }
+
+    #[actix_rt::test]
+    async fn update_description() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        let uuid = User::update_description(
+            UserUpdateDescriptionPayload {
+                user_id: 1,
+                description: Some("Hallo Welt".to_string()),
+            },
+            &mut transaction,
+        )
+        .await
+        .unwrap();
+
+        if let ConcreteUuid::User(user) = uuid.concrete_uuid {
+            assert_eq!(user.description, Some("Hallo Welt".to_string()));
+        } else {
+            panic!("UUID is not a user")
+        }
+    }
+
+    #[actix_rt::test]
+    async fn set_last_login() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        let logins_before = match User::fetch_by_username_via_transaction("admin", &mut transaction)
+            .await
+            .unwrap()
+            .concrete_uuid
+        {
+            ConcreteUuid::User(user) => user.logins,
+            _ => panic!("UUID is not a user"),
+        };
+
+        let uuid = User::set_last_login(
+            UserSetLastLoginPayload {
+                user_id: 1,
+                last_login: Some(DateTime::ymd(2021, 1, 1)),
+            },
+            &mut transaction,
+        )
+        .await
+        .unwrap();
+
+        if let ConcreteUuid::User(user) = uuid.concrete_uuid {
+            assert_eq!(user.logins, logins_before + 1);
+            assert_eq!(user.last_login, Some(DateTime::ymd(2021, 1, 1)));
+        } else {
+            panic!("UUID is not a user")
+        }
+    }
+
+    #[actix_rt::test]
+    async fn set_last_login_of_unknown_user() {
+        let pool = create_database_pool().await.unwrap();
+
+        let result = User::set_last_login(
+            UserSetLastLoginPayload {
+                user_id: 1855,
+                last_login: None,
+            },
+            &pool,
+        )
+        .await;
+
+        assert!(matches!(result, Err(UserMutationError::NotFound)));
+    }
+
+    #[actix_rt::test]
+    async fn hard_delete_user_without_contributions() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        let user_id = User::create(create_payload(), &mut transaction)
+            .await
+            .unwrap()
+            .id;
+
+        let report = User::delete(
+            UserDeletePayload {
+                user_id,
+                mode: UserDeleteMode::HardDelete,
+                dry_run: false,
+            },
+            &mut transaction,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(report.affected_rows.users, 1);
+        assert_eq!(report.affected_rows.user_fields, 1);
+        assert!(matches!(
+            User::fetch_via_transaction(user_id, &mut transaction).await,
+            Err(UuidError::NotFound)
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn hard_delete_user_with_contributions() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        let result = User::delete(
+            UserDeletePayload {
+                user_id: 1,
+                mode: UserDeleteMode::HardDelete,
+                dry_run: false,
+            },
+            &mut transaction,
+        )
+        .await;
+
+        assert!(matches!(result, Err(UserDeleteError::HasContributions)));
+    }
+
+    #[actix_rt::test]
+    async fn hard_delete_user_with_events_about_them() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        let user_id = User::create(create_payload(), &mut transaction)
+            .await
+            .unwrap()
+            .id;
+        insert_event(1, user_id, &mut transaction).await;
+
+        let result = User::delete(
+            UserDeletePayload {
+                user_id,
+                mode: UserDeleteMode::HardDelete,
+                dry_run: false,
+            },
+            &mut transaction,
+        )
+        .await;
+
+        assert!(matches!(result, Err(UserDeleteError::HasContributions)));
+    }
+
+    async fn insert_event<'a>(
+        actor_id: i32,
+        object_id: i32,
+        transaction: &mut sqlx::Transaction<'a, sqlx::MySql>,
+    ) {
+        sqlx::query!(
+            r#"
+                INSERT INTO event_log (actor_id, event_id, uuid_id, instance_id, date)
+                    SELECT ?, e.id, ?, i.id, ?
+                    FROM event e
+                    JOIN instance i
+                    WHERE e.name = 'entity/revision/add' AND i.subdomain = 'de'
+            "#,
+            actor_id,
+            object_id,
+            DateTime::now()
+        )
+        .execute(transaction)
+        .await
+        .unwrap();
+    }
+
+    async fn count_events_of_actor<'a>(
+        actor_id: i32,
+        transaction: &mut sqlx::Transaction<'a, sqlx::MySql>,
+    ) -> i64 {
+        sqlx::query!(
+            r#"SELECT COUNT(*) AS count FROM event_log WHERE actor_id = ?"#,
+            actor_id
+        )
+        .fetch_one(transaction)
+        .await
+        .unwrap()
+        .count
+    }
+
+    #[actix_rt::test]
+    async fn anonymize_user() {
+        let pool = create_database_pool().await.unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+
+        let placeholder_id = match User::fetch_id_by_username(DELETED_USERNAME, &mut transaction)
+            .await
+            .unwrap()
+        {
+            Some(id) => id,
+            None => {
+                User::create(
+                    UserCreatePayload {
+                        username: DELETED_USERNAME.to_string(),
+                        email: "deleted@example.org".to_string(),
+                        description: None,
+                        identity_provider_id: None,
+                    },
+                    &mut transaction,
+                )
+                .await
+                .unwrap()
+                .id
+            }
+        };
+        let user_id = User::create(create_payload(), &mut transaction)
+            .await
+            .unwrap()
+            .id;
+        insert_event(user_id, 1855, &mut transaction).await;
+        insert_event(user_id, 1855, &mut transaction).await;
+        let placeholder_events = count_events_of_actor(placeholder_id, &mut transaction).await;
+
+        let report = User::delete(
+            UserDeletePayload {
+                user_id,
+                mode: UserDeleteMode::Anonymize,
+                dry_run: true,
+            },
+            &mut transaction,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(report.affected_rows.event_logs, 2);
+        assert_eq!(report.affected_rows.users, 1);
+        assert_eq!(report.affected_rows.uuids, 1);
+        // Nothing has been changed in the dry run.
+        assert_eq!(count_events_of_actor(user_id, &mut transaction).await, 2);
+        let uuid = User::fetch_via_transaction(user_id, &mut transaction)
+            .await
+            .unwrap();
+        assert!(!uuid.trashed);
+
+        let report = User::delete(
+            UserDeletePayload {
+                user_id,
+                mode: UserDeleteMode::Anonymize,
+                dry_run: false,
+            },
+            &mut transaction,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(report.affected_rows.event_logs, 2);
+        assert_eq!(count_events_of_actor(user_id, &mut transaction).await, 0);
+        assert_eq!(
+            count_events_of_actor(placeholder_id, &mut transaction).await,
+            placeholder_events + 2
+        );
+        let uuid = User::fetch_via_transaction(user_id, &mut transaction)
+            .await
+            .unwrap();
+        assert!(uuid.trashed);
+        if let ConcreteUuid::User(user) = uuid.concrete_uuid {
+            assert_eq!(user.username, format!("deleted-{}", user_id));
+            assert_eq!(
+                user.email,
+                Some(format!("deleted-{}@deleted.invalid", user_id))
+            );
+            assert!(user.description.is_none());
+            assert!(user.last_login.is_none());
+            assert!(user.roles.is_empty());
+        } else {
+            panic!("UUID is not a user")
+        }
+    }
+
+    #[actix_rt::test]
+    async fn users() {
+        let pool = create_database_pool().await.unwrap();
+
+        let users = Users::fetch(UsersFilter::default(), 3, None, &pool)
+            .await
+            .unwrap()
+            .users;
+
+        assert_eq!(users.len(), 3);
+        assert_eq!(users[0].id, 1);
+        for uuid in users.iter() {
+            let mut fetched = User::fetch(uuid.id, &pool).await.unwrap();
+            User::strip_private_fields(&mut fetched);
+            assert_eq!(uuid.alias, fetched.alias);
+            assert_eq!(uuid.trashed, fetched.trashed);
+            if let (ConcreteUuid::User(user), ConcreteUuid::User(fetched)) =
+                (&uuid.concrete_uuid, &fetched.concrete_uuid)
+            {
+                assert!(user.email.is_none());
+                assert_eq!(user.username, fetched.username);
+                assert_eq!(user.roles, fetched.roles);
+                assert_eq!(user.logins, fetched.logins);
+            } else {
+                panic!("UUID is not a user")
+            }
+        }
+
+        let next = Users::fetch(UsersFilter::default(), 3, Some(users[2].id), &pool)
+            .await
+            .unwrap()
+            .users;
+        assert!(next.iter().all(|user| user.id > users[2].id));
+    }
+
+    #[actix_rt::test]
+    async fn users_by_username_prefix_and_role() {
+        let pool = create_database_pool().await.unwrap();
+
+        let users = Users::fetch(
+            UsersFilter {
+                username_prefix: Some("adm".to_string()),
+                role: Some("sysadmin".to_string()),
+                ..UsersFilter::default()
+            },
+            10,
+            None,
+            &pool,
+        )
+        .await
+        .unwrap()
+        .users;
+
+        assert!(users.iter().any(|user| user.id == 1));
+        for uuid in users.iter() {
+            if let ConcreteUuid::User(user) = &uuid.concrete_uuid {
+                assert!(user.username.starts_with("adm"));
+                assert!(user.roles.iter().any(|role| role == "sysadmin"));
+            }
+        }
+    }
+
+    #[actix_rt::test]
+    async fn exists_username() {
+        let pool = create_database_pool().await.unwrap();
+
+        assert!(User::exists_username("admin", &pool).await.unwrap());
+        assert!(!User::exists_username("does-not-exist", &pool)
+            .await
+            .unwrap());
+    }
 }
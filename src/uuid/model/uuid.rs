@@ -25,8 +25,8 @@ pub struct Uuid {
 #[derive(Debug, Serialize)]
 #[serde(untagged)]
 pub enum ConcreteUuid {
-    Attachment,
-    BlogPost,
+    Attachment(Attachment),
+    BlogPost(BlogPost),
     Comment(Comment),
     Entity(Entity),
     EntityRevision(EntityRevision),
@@ -160,8 +160,8 @@ impl Uuid {
             Discriminator::EntityRevision => {
                 EntityRevision::fetch_canonical_subject(id, pool).await?
             }
-            Discriminator::Page => None,         // TODO:
-            Discriminator::PageRevision => None, // TODO:
+            Discriminator::Page => Page::get_context(),
+            Discriminator::PageRevision => PageRevision::get_context(),
             Discriminator::TaxonomyTerm => TaxonomyTerm::fetch_canonical_subject(id, pool).await?,
             Discriminator::User => User::get_context(),
         };
@@ -190,8 +190,8 @@ impl Uuid {
                 EntityRevision::fetch_canonical_subject_via_transaction(id, &mut transaction)
                     .await?
             }
-            Discriminator::Page => None,         // TODO:
-            Discriminator::PageRevision => None, // TODO:
+            Discriminator::Page => Page::get_context(),
+            Discriminator::PageRevision => PageRevision::get_context(),
             Discriminator::TaxonomyTerm => {
                 TaxonomyTerm::fetch_canonical_subject(id, &mut transaction).await?
             }
@@ -9,7 +9,11 @@ use crate::message::MessageResponder;
 #[get("/uuid/{id}")]
 async fn uuid(id: web::Path<i32>, db_pool: web::Data<MySqlPool>) -> impl Responder {
     let id = id.into_inner();
-    let message = UuidQuery { id };
+    let message = UuidQuery {
+        id,
+        include_email: false,
+        include_activity: false,
+    };
     let connection = Connection::Pool(db_pool.get_ref());
     message.handle(connection).await
 }